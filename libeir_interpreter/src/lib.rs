@@ -16,4 +16,7 @@ mod process;
 
 mod module;
 
+mod repl;
+pub use repl::{continuation_needed, parse_command, ReplCommand, ReplParseError, WatchExpr, WatchId, WatchList};
+
 //mod trace;
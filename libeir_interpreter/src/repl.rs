@@ -0,0 +1,531 @@
+//! Command parsing for an interactive stepping REPL over the
+//! interpreter.
+//!
+//! This only covers the part of the REPL that doesn't need to look
+//! inside a running `VMState`: turning a line of user input into a
+//! [`ReplCommand`], deciding when multi-line input is still
+//! unterminated via [`continuation_needed`], and tracking the user's
+//! registered [`WatchExpr`]s.
+//!
+//! The interactive driver loop itself *is* implemented here, in
+//! [`run_repl`] - but only up to the boundary of what needs to look
+//! inside a running `VMState`. That execution (actually stepping,
+//! evaluating a `print`/`watch` expression, rendering a backtrace) is
+//! abstracted behind the [`ReplDriver`] trait rather than called
+//! directly, since `vm.rs` (its process/mailbox/stack fields) isn't
+//! part of this snapshot - `lib.rs` declares `mod vm;` but the file
+//! itself was never carried over. A real caller implements
+//! `ReplDriver` against `VMState` and hands it to `run_repl`; `tests`
+//! below exercises the loop with a small in-memory driver instead.
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+/// A single line of REPL input, parsed into the action it requests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplCommand {
+    /// `step` / `s`: execute a single VM instruction.
+    Step,
+    /// `next` / `n`: execute until control returns to the current
+    /// stack frame (steps over calls instead of into them).
+    Next,
+    /// `continue` / `c`: run until the next breakpoint.
+    Continue,
+    /// `break <module>:<function>/<arity>`: set a breakpoint.
+    Break { module: String, function: String, arity: usize },
+    /// `print <expr>` / `p <expr>`: evaluate and print a term
+    /// expression in the current frame, using the textual value syntax
+    /// shared with `lower` (`text::ast::Value`).
+    Print { expr: String },
+    /// `watch <expr>`: register a watch expression, re-evaluated and
+    /// reported every time it changes.
+    Watch { expr: String },
+    /// `unwatch <id>`: remove a previously registered watch.
+    Unwatch { id: WatchId },
+    /// `watches`: list all registered watch expressions.
+    ListWatches,
+    /// `backtrace` / `bt`: print the current call stack.
+    Backtrace,
+    /// `quit` / `q`: exit the REPL.
+    Quit,
+}
+
+/// Whether `accumulated` - the REPL input gathered so far, possibly
+/// already spanning several lines - is unterminated and needs another
+/// line appended before being handed to [`parse_command`]. A
+/// `print`/`watch` expression (the only commands that take free-form
+/// text, using the `text::ast::Value` syntax) can open a `{`/`[`/`(`
+/// or a `"` string on one line and close it on a later one; this
+/// tracks bracket/quote balance over the raw text rather than parsing
+/// the expression itself, so it doesn't need anything from `vm.rs`.
+pub fn continuation_needed(accumulated: &str) -> bool {
+    let mut depth: i64 = 0;
+    let mut in_string = false;
+    let mut chars = accumulated.chars();
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' => in_string = true,
+            '{' | '[' | '(' => depth += 1,
+            '}' | ']' | ')' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    in_string || depth > 0
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplParseError {
+    pub input: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ReplParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "couldn't parse `{}`: {}", self.input, self.reason)
+    }
+}
+
+/// Parses one line of REPL input. Commands may span multiple lines
+/// when an argument (e.g. a `print`/`watch` expression) is left
+/// unterminated; callers should keep accumulating lines with
+/// [`continuation_needed`] until it returns `false` before calling
+/// this.
+pub fn parse_command(line: &str) -> Result<ReplCommand, ReplParseError> {
+    let line = line.trim();
+    let (head, rest) = match line.split_once(char::is_whitespace) {
+        Some((h, r)) => (h, r.trim()),
+        None => (line, ""),
+    };
+
+    let err = |reason: &str| ReplParseError {
+        input: line.to_string(),
+        reason: reason.to_string(),
+    };
+
+    match head {
+        "step" | "s" => Ok(ReplCommand::Step),
+        "next" | "n" => Ok(ReplCommand::Next),
+        "continue" | "c" => Ok(ReplCommand::Continue),
+        "backtrace" | "bt" => Ok(ReplCommand::Backtrace),
+        "quit" | "q" => Ok(ReplCommand::Quit),
+        "watches" => Ok(ReplCommand::ListWatches),
+        "print" | "p" => {
+            if rest.is_empty() {
+                return Err(err("`print` needs an expression"));
+            }
+            Ok(ReplCommand::Print { expr: rest.to_string() })
+        }
+        "watch" => {
+            if rest.is_empty() {
+                return Err(err("`watch` needs an expression"));
+            }
+            Ok(ReplCommand::Watch { expr: rest.to_string() })
+        }
+        "unwatch" => {
+            let id: u32 = rest
+                .parse()
+                .map_err(|_| err("`unwatch` needs a watch id"))?;
+            Ok(ReplCommand::Unwatch { id: WatchId(id) })
+        }
+        "break" => {
+            let (mfa, _) = rest.split_once(|c: char| c.is_whitespace()).unwrap_or((rest, ""));
+            let (module, rest) = mfa
+                .split_once(':')
+                .ok_or_else(|| err("expected `module:function/arity`"))?;
+            let (function, arity) = rest
+                .split_once('/')
+                .ok_or_else(|| err("expected `module:function/arity`"))?;
+            let arity: usize = arity
+                .parse()
+                .map_err(|_| err("arity must be a non-negative integer"))?;
+            Ok(ReplCommand::Break {
+                module: module.to_string(),
+                function: function.to_string(),
+                arity,
+            })
+        }
+        "" => Err(err("empty command")),
+        other => Err(err(&format!("unknown command `{}`", other))),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WatchId(u32);
+
+/// A watch expression the user registered, and the last value it was
+/// seen to evaluate to (as rendered text - the interpreter's `Term`
+/// isn't part of this snapshot, so watches are tracked generically
+/// here rather than over a concrete term type).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchExpr {
+    pub id: WatchId,
+    pub expr: String,
+    pub last_value: Option<String>,
+}
+
+/// Tracks the REPL's registered watch expressions and reports which
+/// ones changed value since the last check.
+#[derive(Debug, Clone, Default)]
+pub struct WatchList {
+    watches: Vec<WatchExpr>,
+    next_id: u32,
+}
+
+impl WatchList {
+    pub fn new() -> Self {
+        WatchList {
+            watches: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn add(&mut self, expr: String) -> WatchId {
+        let id = WatchId(self.next_id);
+        self.next_id += 1;
+        self.watches.push(WatchExpr {
+            id,
+            expr,
+            last_value: None,
+        });
+        id
+    }
+
+    pub fn remove(&mut self, id: WatchId) -> bool {
+        let len = self.watches.len();
+        self.watches.retain(|w| w.id != id);
+        self.watches.len() != len
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &WatchExpr> {
+        self.watches.iter()
+    }
+
+    /// Records a freshly-evaluated value for `id`, returning the watch
+    /// if its value changed since the last call (or this is its first
+    /// evaluation).
+    pub fn update(&mut self, id: WatchId, value: String) -> Option<&WatchExpr> {
+        let watch = self.watches.iter_mut().find(|w| w.id == id)?;
+        if watch.last_value.as_deref() != Some(value.as_str()) {
+            watch.last_value = Some(value);
+            Some(watch)
+        } else {
+            None
+        }
+    }
+}
+
+/// Everything [`run_repl`] needs from a running VM, kept separate from
+/// `ReplCommand`/`parse_command` so this module doesn't have to know
+/// `VMState`'s shape. A caller with access to the real interpreter
+/// state implements this against it; `tests` below implements it
+/// against a small in-memory stand-in.
+pub trait ReplDriver {
+    /// Executes a single VM instruction.
+    fn step(&mut self);
+    /// Executes until control returns to the current stack frame.
+    fn next(&mut self);
+    /// Runs until the next breakpoint.
+    fn cont(&mut self);
+    /// Registers a breakpoint at `module:function/arity`.
+    fn set_breakpoint(&mut self, module: &str, function: &str, arity: usize);
+    /// Evaluates `expr` (in the `text::ast::Value` syntax) against the
+    /// current frame, rendering the result as text.
+    fn eval(&mut self, expr: &str) -> String;
+    /// Renders the current call stack.
+    fn backtrace(&mut self) -> String;
+}
+
+/// Runs the read-eval-print loop: reads lines from `input`, joining
+/// them with [`continuation_needed`] until a command is complete,
+/// parses each with [`parse_command`], and dispatches it to `driver`
+/// (for anything that needs VM state) or to a local [`WatchList`] (for
+/// `watch`/`unwatch`/`watches`, which this module can already track on
+/// its own). Returns once `quit` is entered or `input` reaches EOF.
+pub fn run_repl<D, R, W>(driver: &mut D, input: &mut R, output: &mut W) -> io::Result<()>
+where
+    D: ReplDriver,
+    R: BufRead,
+    W: Write,
+{
+    let mut watches = WatchList::new();
+    let mut accumulated = String::new();
+
+    loop {
+        write!(output, "{}", if accumulated.is_empty() { "> " } else { "... " })?;
+        output.flush()?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            // EOF with an unterminated command left in `accumulated`:
+            // nothing more will ever arrive to close it, so just stop.
+            return Ok(());
+        }
+        let line = line.trim_end_matches(['\n', '\r']);
+
+        if !accumulated.is_empty() {
+            accumulated.push('\n');
+        }
+        accumulated.push_str(line);
+
+        if continuation_needed(&accumulated) {
+            continue;
+        }
+
+        let command_input = std::mem::take(&mut accumulated);
+        match parse_command(&command_input) {
+            Ok(ReplCommand::Quit) => return Ok(()),
+            Ok(command) => dispatch(driver, &mut watches, command, output)?,
+            Err(error) => writeln!(output, "{}", error)?,
+        }
+    }
+}
+
+fn dispatch<D, W>(
+    driver: &mut D,
+    watches: &mut WatchList,
+    command: ReplCommand,
+    output: &mut W,
+) -> io::Result<()>
+where
+    D: ReplDriver,
+    W: Write,
+{
+    let resumes_vm = matches!(
+        command,
+        ReplCommand::Step | ReplCommand::Next | ReplCommand::Continue
+    );
+    match command {
+        ReplCommand::Step => driver.step(),
+        ReplCommand::Next => driver.next(),
+        ReplCommand::Continue => driver.cont(),
+        ReplCommand::Break { module, function, arity } => {
+            driver.set_breakpoint(&module, &function, arity)
+        }
+        ReplCommand::Print { expr } => writeln!(output, "{}", driver.eval(&expr))?,
+        ReplCommand::Watch { expr } => {
+            let id = watches.add(expr);
+            writeln!(output, "watch {} added", id.0)?;
+        }
+        ReplCommand::Unwatch { id } => {
+            if watches.remove(id) {
+                writeln!(output, "watch {} removed", id.0)?;
+            } else {
+                writeln!(output, "no such watch {}", id.0)?;
+            }
+        }
+        ReplCommand::ListWatches => {
+            for watch in watches.iter() {
+                writeln!(output, "{}: {}", watch.id.0, watch.expr)?;
+            }
+        }
+        ReplCommand::Backtrace => writeln!(output, "{}", driver.backtrace())?,
+        ReplCommand::Quit => unreachable!("handled by run_repl before dispatch"),
+    }
+    if resumes_vm {
+        // Re-evaluate every watch once the VM stops, reporting those
+        // whose value changed.
+        let registered: Vec<(WatchId, String)> =
+            watches.iter().map(|w| (w.id, w.expr.clone())).collect();
+        for (id, expr) in registered {
+            let value = driver.eval(&expr);
+            if let Some(watch) = watches.update(id, value) {
+                let value = watch.last_value.as_deref().unwrap_or("");
+                writeln!(output, "watch {}: {} = {}", watch.id.0, watch.expr, value)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        continuation_needed, parse_command, run_repl, ReplCommand, ReplDriver, WatchId, WatchList,
+    };
+    use std::io::Cursor;
+
+    #[test]
+    fn complete_single_line_needs_no_continuation() {
+        assert!(!continuation_needed("step"));
+        assert!(!continuation_needed("print {ok, 1}"));
+    }
+
+    #[test]
+    fn unbalanced_brackets_need_continuation() {
+        assert!(continuation_needed("print {ok,"));
+        assert!(continuation_needed("watch [a, b"));
+        assert!(continuation_needed("print {ok, [1, 2"));
+    }
+
+    #[test]
+    fn closing_a_later_line_ends_the_continuation() {
+        let mut accumulated = String::from("print {ok,\n");
+        assert!(continuation_needed(&accumulated));
+        accumulated.push_str(" 1}");
+        assert!(!continuation_needed(&accumulated));
+    }
+
+    #[test]
+    fn unterminated_string_needs_continuation() {
+        assert!(continuation_needed("print \"woo"));
+        assert!(!continuation_needed("print \"woo\""));
+    }
+
+    #[test]
+    fn escaped_quote_does_not_close_the_string() {
+        assert!(continuation_needed("print \"woo\\\""));
+    }
+
+    #[test]
+    fn parses_every_zero_argument_command() {
+        assert_eq!(parse_command("step").unwrap(), ReplCommand::Step);
+        assert_eq!(parse_command("s").unwrap(), ReplCommand::Step);
+        assert_eq!(parse_command("next").unwrap(), ReplCommand::Next);
+        assert_eq!(parse_command("continue").unwrap(), ReplCommand::Continue);
+        assert_eq!(parse_command("backtrace").unwrap(), ReplCommand::Backtrace);
+        assert_eq!(parse_command("bt").unwrap(), ReplCommand::Backtrace);
+        assert_eq!(parse_command("watches").unwrap(), ReplCommand::ListWatches);
+        assert_eq!(parse_command("quit").unwrap(), ReplCommand::Quit);
+        assert_eq!(parse_command("q").unwrap(), ReplCommand::Quit);
+    }
+
+    #[test]
+    fn parses_break_with_module_function_arity() {
+        assert_eq!(
+            parse_command("break foo:bar/2").unwrap(),
+            ReplCommand::Break {
+                module: "foo".to_string(),
+                function: "bar".to_string(),
+                arity: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_break() {
+        assert!(parse_command("break foo").is_err());
+        assert!(parse_command("break foo:bar").is_err());
+        assert!(parse_command("break foo:bar/notanumber").is_err());
+    }
+
+    #[test]
+    fn rejects_empty_and_unknown_commands() {
+        assert!(parse_command("").is_err());
+        assert!(parse_command("frobnicate").is_err());
+        assert!(parse_command("print").is_err());
+        assert!(parse_command("watch").is_err());
+        assert!(parse_command("unwatch not-a-number").is_err());
+    }
+
+    #[test]
+    fn watch_list_tracks_additions_removals_and_value_changes() {
+        let mut watches = WatchList::new();
+        let id = watches.add("X".to_string());
+
+        // First observation of a value always reports as a change.
+        assert!(watches.update(id, "1".to_string()).is_some());
+        // Same value again: no change.
+        assert!(watches.update(id, "1".to_string()).is_none());
+        // Different value: reports again.
+        assert!(watches.update(id, "2".to_string()).is_some());
+
+        assert_eq!(watches.iter().count(), 1);
+        assert!(watches.remove(id));
+        assert_eq!(watches.iter().count(), 0);
+        // Removing an already-removed (or never-registered) id fails.
+        assert!(!watches.remove(id));
+        assert!(!watches.remove(WatchId(id.0.wrapping_add(1))));
+    }
+
+    /// A minimal [`ReplDriver`] standing in for `VMState`: records
+    /// every call it receives instead of touching any real VM state.
+    #[derive(Default)]
+    struct RecordingDriver {
+        log: Vec<String>,
+    }
+
+    impl ReplDriver for RecordingDriver {
+        fn step(&mut self) {
+            self.log.push("step".to_string());
+        }
+        fn next(&mut self) {
+            self.log.push("next".to_string());
+        }
+        fn cont(&mut self) {
+            self.log.push("continue".to_string());
+        }
+        fn set_breakpoint(&mut self, module: &str, function: &str, arity: usize) {
+            self.log.push(format!("break {}:{}/{}", module, function, arity));
+        }
+        fn eval(&mut self, expr: &str) -> String {
+            self.log.push(format!("eval {}", expr));
+            format!("<{}>", expr)
+        }
+        fn backtrace(&mut self) -> String {
+            self.log.push("backtrace".to_string());
+            "#0 foo:bar/0".to_string()
+        }
+    }
+
+    fn run(script: &str, driver: &mut RecordingDriver) -> String {
+        let mut input = Cursor::new(script.as_bytes().to_vec());
+        let mut output = Vec::new();
+        run_repl(driver, &mut input, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn drives_step_and_backtrace_through_to_the_driver() {
+        let mut driver = RecordingDriver::default();
+        let transcript = run("step\nbacktrace\nquit\n", &mut driver);
+
+        assert_eq!(driver.log, vec!["step".to_string(), "backtrace".to_string()]);
+        assert!(transcript.contains("#0 foo:bar/0"));
+    }
+
+    #[test]
+    fn quit_stops_the_loop_without_reaching_later_input() {
+        let mut driver = RecordingDriver::default();
+        run("quit\nstep\n", &mut driver);
+        assert!(driver.log.is_empty());
+    }
+
+    #[test]
+    fn eof_stops_the_loop_just_like_quit() {
+        let mut driver = RecordingDriver::default();
+        let transcript = run("step\n", &mut driver);
+        assert_eq!(driver.log, vec!["step".to_string()]);
+        assert!(transcript.contains("> "));
+    }
+
+    #[test]
+    fn multi_line_print_is_accumulated_before_dispatch() {
+        let mut driver = RecordingDriver::default();
+        run("print {ok,\n 1}\nquit\n", &mut driver);
+        assert_eq!(driver.log, vec!["eval {ok,\n 1}".to_string()]);
+    }
+
+    #[test]
+    fn watch_commands_are_handled_locally_without_touching_the_driver() {
+        let mut driver = RecordingDriver::default();
+        let transcript = run("watch X\nwatches\nunwatch 0\nquit\n", &mut driver);
+
+        assert!(driver.log.is_empty(), "watch bookkeeping shouldn't reach the driver");
+        assert!(transcript.contains("watch 0 added"));
+        assert!(transcript.contains("0: X"));
+        assert!(transcript.contains("watch 0 removed"));
+    }
+}
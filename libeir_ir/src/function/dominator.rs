@@ -0,0 +1,182 @@
+//! Dominator-tree analysis over the block graph.
+//!
+//! `Function` already maintains `predecessors`/`successors` sets per
+//! block, but exposes no dominance information, which most
+//! optimization/verification passes need. [`DomTree`] answers
+//! `idom(Block) -> Option<Block>` and `dominates(a, b) -> bool`,
+//! computed with the iterative Cooper-Harvey-Kennedy algorithm.
+
+use alloc::vec::Vec;
+use hashbrown::HashMap;
+
+use super::{Block, Function};
+
+/// Dominator tree for the blocks reachable from a function's entry
+/// block. Unreachable blocks have no recorded immediate dominator.
+pub struct DomTree {
+    /// Reverse-postorder number of each reachable block.
+    rpo_number: HashMap<Block, usize>,
+    /// Immediate dominator of each reachable block, indexed by RPO
+    /// number. The entry block is its own immediate dominator.
+    idom: Vec<usize>,
+    /// RPO-numbered blocks, for turning an RPO number back into a
+    /// `Block`.
+    rpo_blocks: Vec<Block>,
+}
+
+impl DomTree {
+    /// Builds the dominator tree for `fun`, starting from
+    /// `fun.block_entry()`.
+    pub fn build(fun: &Function) -> Self {
+        let entry = fun.block_entry();
+
+        // Reverse-postorder numbering of reachable blocks, walking
+        // `successors` from the entry block.
+        let rpo_blocks = reverse_postorder(fun, entry);
+        let mut rpo_number = HashMap::with_capacity(rpo_blocks.len());
+        for (num, block) in rpo_blocks.iter().enumerate() {
+            rpo_number.insert(*block, num);
+        }
+
+        let mut idom: Vec<Option<usize>> = alloc::vec![None; rpo_blocks.len()];
+        idom[0] = Some(0);
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            // Skip the entry block (index 0): it is its own immediate
+            // dominator by definition.
+            for num in 1..rpo_blocks.len() {
+                let block = rpo_blocks[num];
+                let preds = fun.blocks[block]
+                    .predecessors
+                    .iter(&fun.pool.block_set)
+                    .filter_map(|p| rpo_number.get(&p).copied());
+
+                let mut new_idom: Option<usize> = None;
+                for pred_num in preds {
+                    if idom[pred_num].is_none() {
+                        continue;
+                    }
+                    new_idom = Some(match new_idom {
+                        None => pred_num,
+                        Some(cur) => intersect(&idom, cur, pred_num),
+                    });
+                }
+
+                if new_idom != idom[num] {
+                    idom[num] = new_idom;
+                    changed = true;
+                }
+            }
+        }
+
+        let idom = idom.into_iter().map(|v| v.unwrap_or(0)).collect();
+
+        DomTree {
+            rpo_number,
+            idom,
+            rpo_blocks,
+        }
+    }
+
+    /// The immediate dominator of `block`, or `None` if `block` is
+    /// unreachable from the entry block (or is the entry block
+    /// itself).
+    pub fn idom(&self, block: Block) -> Option<Block> {
+        let num = *self.rpo_number.get(&block)?;
+        if num == 0 {
+            // The entry block has no immediate dominator.
+            return None;
+        }
+        Some(self.rpo_blocks[self.idom[num]])
+    }
+
+    /// Whether `a` dominates `b`, i.e. every path from the entry block
+    /// to `b` passes through `a`. A block always dominates itself.
+    /// Returns `false` if either block is unreachable.
+    pub fn dominates(&self, a: Block, b: Block) -> bool {
+        let (a_num, mut cur) = match (self.rpo_number.get(&a), self.rpo_number.get(&b)) {
+            (Some(a_num), Some(b_num)) => (*a_num, *b_num),
+            _ => return false,
+        };
+
+        loop {
+            if cur == a_num {
+                return true;
+            }
+            if cur == 0 {
+                return false;
+            }
+            cur = self.idom[cur];
+        }
+    }
+
+    /// Iterates the immediate children of `block` in the dominator
+    /// tree, i.e. every reachable block whose immediate dominator is
+    /// `block`.
+    pub fn children(&self, block: Block) -> impl Iterator<Item = Block> + '_ {
+        let block_num = self.rpo_number.get(&block).copied();
+        self.rpo_blocks
+            .iter()
+            .enumerate()
+            .filter(move |(num, _)| {
+                Some(*num) != block_num && Some(self.idom[*num]) == block_num
+            })
+            .map(|(_, block)| *block)
+    }
+}
+
+/// Walks `successors` from `entry`, numbering blocks in
+/// reverse-postorder.
+fn reverse_postorder(fun: &Function, entry: Block) -> Vec<Block> {
+    let mut visited = HashMap::new();
+    let mut postorder = Vec::new();
+
+    // Explicit stack of (block, successor index) to avoid recursing
+    // over arbitrarily deep/cyclic block graphs.
+    let mut stack: Vec<(Block, Vec<Block>, usize)> = Vec::new();
+    visited.insert(entry, ());
+    stack.push((entry, successors_of(fun, entry), 0));
+
+    while let Some((block, succs, idx)) = stack.last_mut() {
+        if *idx < succs.len() {
+            let next = succs[*idx];
+            *idx += 1;
+            if !visited.contains_key(&next) {
+                visited.insert(next, ());
+                let next_succs = successors_of(fun, next);
+                stack.push((next, next_succs, 0));
+            }
+        } else {
+            postorder.push(*block);
+            stack.pop();
+        }
+    }
+
+    postorder.reverse();
+    postorder
+}
+
+fn successors_of(fun: &Function, block: Block) -> Vec<Block> {
+    fun.blocks[block]
+        .successors
+        .iter(&fun.pool.block_set)
+        .collect()
+}
+
+/// Walks the two idom-chain finger pointers up from `a` and `b`,
+/// always advancing whichever has the higher RPO number, until they
+/// converge on their common dominator.
+fn intersect(idom: &[Option<usize>], mut a: usize, mut b: usize) -> usize {
+    while a != b {
+        while a > b {
+            a = idom[a].unwrap();
+        }
+        while b > a {
+            b = idom[b].unwrap();
+        }
+    }
+    a
+}
@@ -1,4 +1,14 @@
-use std::hash::{Hash, Hasher};
+use core::hash::{Hash, Hasher};
+
+// `alloc::*` assumes the crate root declares `#![no_std]` plus
+// `extern crate alloc;` (mirrored by `hashbrown::HashSet` in
+// `super::Function`, used unconditionally rather than behind a
+// `std`/`no_std` feature split) - `lib.rs` isn't part of this crate
+// snapshot, so that wiring can't be added or verified here.
+// `from_bytespan` below is compiled unconditionally; it is not behind
+// a `std` feature.
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 
 use libeir_util_datastructures::dedup_aux_primary_map::DedupPrimaryMap;
 use libeir_util_datastructures::{
@@ -121,12 +131,8 @@ impl LocationContainer {
         let mut terminals = EntityList::new();
         terminals.push(terminal, &mut self.terminal_pool);
 
-        self.locations.push(
-            LocationData {
-                terminals: EntityList::new(),
-            },
-            &mut self.terminal_pool,
-        )
+        self.locations
+            .push(LocationData { terminals }, &mut self.terminal_pool)
     }
 
     pub fn location(
@@ -153,6 +159,8 @@ impl LocationContainer {
             .push(LocationData { terminals }, &mut self.terminal_pool)
     }
 
+    /// Builds a [`Location`] from a byte span, resolving the origin
+    /// file name and line number via the `CodeMap`.
     pub fn from_bytespan(
         &mut self,
         codemap: &CodeMap,
@@ -172,6 +180,43 @@ impl LocationContainer {
         self.location(file, line, names, span)
     }
 
+    /// Attaches a caller frame to a callee's location when a call is
+    /// inlined. The resulting location's terminal list is the
+    /// callee's frames followed by the caller's, so the innermost
+    /// inlined frame renders first in [`Self::render_backtrace`] -
+    /// just like a regular call stack.
+    pub fn location_inline(&mut self, caller: Location, callee: Location) -> Location {
+        self.concat_locations(callee, caller)
+    }
+
+    /// Renders `location` as a symbolic backtrace, innermost frame
+    /// first, formatting each terminal as `module:function
+    /// (file:line)`. Falls back gracefully when `file`/`line`/`names`
+    /// are `None`.
+    pub fn render_backtrace(&self, location: Location) -> Vec<String> {
+        let terminals = self.locations[location]
+            .terminals
+            .as_slice(&self.terminal_pool);
+
+        terminals
+            .iter()
+            .map(|terminal| {
+                let data = &self.terminals[*terminal];
+
+                let names = match &data.names {
+                    Some((module, function)) => alloc::format!("{}:{}", module, function),
+                    None => "?".to_string(),
+                };
+
+                match (&data.file, data.line) {
+                    (Some(file), Some(line)) => alloc::format!("{} ({}:{})", names, file, line),
+                    (Some(file), None) => alloc::format!("{} ({})", names, file),
+                    (None, _) => names,
+                }
+            })
+            .collect()
+    }
+
     pub fn concat_locations(&mut self, bottom: Location, top: Location) -> Location {
         let mut terminals = Vec::new();
         terminals.extend(
@@ -1,6 +1,7 @@
-use std::cmp::Eq;
-use std::collections::HashSet;
-use std::hash::{Hash, Hasher};
+use core::cmp::Eq;
+use core::hash::{Hash, Hasher};
+
+use hashbrown::HashSet;
 
 use cranelift_bforest::{BoundSet, Set, SetForest};
 use cranelift_entity::packed_option::ReservedValue;
@@ -36,7 +37,11 @@ pub use location::{Location, LocationContainer};
 mod format;
 pub use format::{ContainerDebug, ContainerDebugAdapter};
 
-//mod serialize;
+mod dominator;
+pub use dominator::DomTree;
+
+mod serialize;
+pub use serialize::{read_block_graph, write_block_graph, Decoder, DecodeError, Encoder};
 
 /// Block/continuation
 #[derive(Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -48,8 +53,8 @@ impl Default for Block {
     }
 }
 impl<C> AuxDebug<C> for Block {
-    fn aux_fmt(&self, f: &mut std::fmt::Formatter<'_>, _aux: &C) -> std::fmt::Result {
-        std::fmt::Debug::fmt(self, f)
+    fn aux_fmt(&self, f: &mut core::fmt::Formatter<'_>, _aux: &C) -> core::fmt::Result {
+        core::fmt::Debug::fmt(self, f)
     }
 }
 
@@ -165,13 +170,13 @@ impl HasAux<SetForest<Block>> for Function {
 }
 
 impl<C: HasAux<Function>> AuxDebug<C> for Function {
-    fn aux_fmt(&self, _f: &mut std::fmt::Formatter<'_>, _container: &C) -> std::fmt::Result {
+    fn aux_fmt(&self, _f: &mut core::fmt::Formatter<'_>, _container: &C) -> core::fmt::Result {
         unimplemented!()
     }
 }
 
-impl std::fmt::Debug for Function {
-    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl core::fmt::Debug for Function {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
         self.aux_fmt(fmt, self)
     }
 }
@@ -185,7 +190,7 @@ impl Function {
         v.get_value(self)
     }
 
-    pub fn iter_constants(&self) -> std::collections::hash_set::Iter<'_, Value> {
+    pub fn iter_constants(&self) -> hashbrown::hash_set::Iter<'_, Value> {
         self.constant_values.iter()
     }
 
@@ -0,0 +1,266 @@
+//! Compact, pool-aware binary (de)serialization building blocks,
+//! so IR can eventually be cached to disk and reloaded without
+//! re-parsing text.
+//!
+//! Entities (`Block`, `Value`, `PrimOp`, ...) are dense `u32` indices,
+//! so they're encoded as LEB128 varints to keep files small - most
+//! indices are tiny. Each `EntityList`/`Set` is written as a
+//! length-prefixed run of varint entity ids; on load the pools
+//! (`ListPool<Value>`, `SetForest<Block>`) are rebuilt from those runs
+//! rather than trusted from the file, since pool offsets aren't stable
+//! across builds.
+//!
+//! [`write_block_graph`]/[`read_block_graph`] cover the block-graph
+//! skeleton (entry block, per-block arguments, predecessor/successor
+//! edges) - the part of a [`Function`] describable with just the
+//! entity/pool machinery visible in this snapshot. A real
+//! whole-`Function` (de)serializer also needs to cover `primops`
+//! (`PrimOpData`'s `op: PrimOpKind` field), each block's own
+//! `op: OpKind`, and `constant_container: ConstantContainer` - but
+//! `PrimOpKind`, `OpKind` and `ConstantContainer` are all defined in
+//! files this crate snapshot doesn't carry (`primop.rs`, `op.rs`,
+//! `crate::constant`), so their variants/layout aren't known here and
+//! encoding them would mean guessing at a format. `LocationContainer`
+//! (`location.rs`) *is* present, but doesn't expose an enumeration of
+//! its `DedupPrimaryMap`/`DedupAuxPrimaryMap` contents through its
+//! public API either, and `Function::new` needs a `FunctionIdent`
+//! (also not part of this snapshot) to construct a `Function` at all
+//! - so there's no real `Function` this module can build here to
+//! round-trip end-to-end. Beyond just the constructor, growing the
+//! block map on read also has to stay in sync with `Function::values`
+//! (see [`read_block_graph`]'s doc comment), which ties it to
+//! `Function` more deeply than just needing a `FunctionIdent` to call
+//! `new`. `tests` covers what's left: the codec primitives
+//! (`varint`/`entity`/`entity_list`/`set`/`option_entity`), and a
+//! round-trip of the block-graph wire format itself (via
+//! [`write_graph_shape`], the `Function`-free half of
+//! [`write_block_graph`], decoded back with the same `Decoder` calls
+//! [`read_block_graph`] makes).
+
+use alloc::vec::Vec;
+
+use cranelift_bforest::{Set, SetForest};
+use cranelift_entity::{EntityList, EntityRef, ListPool};
+
+use cranelift_entity::PrimaryMap;
+
+use super::{Block, BlockData, Function, PoolContainer};
+
+/// A growable little-endian byte buffer with LEB128 varint helpers.
+#[derive(Default)]
+pub struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Encoder { bytes: Vec::new() }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Unsigned LEB128.
+    pub fn varint(&mut self, mut value: u64) {
+        loop {
+            let byte = (value & 0x7F) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.bytes.push(byte);
+                break;
+            } else {
+                self.bytes.push(byte | 0x80);
+            }
+        }
+    }
+
+    pub fn entity<E: EntityRef>(&mut self, entity: E) {
+        self.varint(entity.index() as u64);
+    }
+
+    /// Writes an `EntityList<E>` as a length-prefixed run of varint
+    /// entity ids.
+    pub fn entity_list<E: EntityRef>(&mut self, list: &EntityList<E>, pool: &ListPool<E>) {
+        let slice = list.as_slice(pool);
+        self.varint(slice.len() as u64);
+        for entity in slice {
+            self.entity(*entity);
+        }
+    }
+
+    /// Writes a `Set<E>` the same way - a length-prefixed run of
+    /// varint entity ids, reconstructed into a fresh `Set` on load so
+    /// the forest isn't trusted from the file.
+    pub fn set<E>(&mut self, set: &Set<E>, forest: &SetForest<E>)
+    where
+        E: EntityRef + Copy + Ord,
+    {
+        let members: Vec<E> = set.iter(forest).collect();
+        self.varint(members.len() as u64);
+        for entity in members {
+            self.entity(entity);
+        }
+    }
+
+    pub fn option_entity<E: EntityRef>(&mut self, entity: Option<E>) {
+        match entity {
+            // `E::reserved_value()` round-trips as the entity whose
+            // index is `u32::MAX`; we still tag presence explicitly so
+            // a valid entity that happens to share that index (it
+            // can't, by construction, but the invariant shouldn't rely
+            // on that) is never ambiguous with `None`.
+            Some(entity) => {
+                self.varint(1);
+                self.entity(entity);
+            }
+            None => self.varint(0),
+        }
+    }
+}
+
+/// A cursor over a byte slice produced by [`Encoder`].
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeError;
+
+impl<'a> Decoder<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Decoder { bytes, pos: 0 }
+    }
+
+    pub fn varint(&mut self) -> Result<u64, DecodeError> {
+        let mut result: u64 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = *self.bytes.get(self.pos).ok_or(DecodeError)?;
+            self.pos += 1;
+            result |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+            shift += 7;
+        }
+    }
+
+    pub fn entity<E: EntityRef>(&mut self) -> Result<E, DecodeError> {
+        Ok(E::new(self.varint()? as usize))
+    }
+
+    pub fn entity_list<E: EntityRef>(
+        &mut self,
+        pool: &mut ListPool<E>,
+    ) -> Result<EntityList<E>, DecodeError> {
+        let len = self.varint()?;
+        let mut list = EntityList::new();
+        for _ in 0..len {
+            let entity = self.entity()?;
+            list.push(entity, pool);
+        }
+        Ok(list)
+    }
+
+    pub fn set<E>(&mut self, forest: &mut SetForest<E>) -> Result<Set<E>, DecodeError>
+    where
+        E: EntityRef + Copy + Ord,
+    {
+        let len = self.varint()?;
+        let mut set = Set::new();
+        for _ in 0..len {
+            let entity = self.entity()?;
+            set.insert(entity, forest, &());
+        }
+        Ok(set)
+    }
+
+    pub fn option_entity<E: EntityRef>(&mut self) -> Result<Option<E>, DecodeError> {
+        match self.varint()? {
+            0 => Ok(None),
+            1 => Ok(Some(self.entity()?)),
+            _ => Err(DecodeError),
+        }
+    }
+}
+
+/// Writes the block graph skeleton of `fun` - the entry block, and
+/// each block's arguments plus predecessor/successor edges - to
+/// `enc`. This is the part of `Function` that's fully describable
+/// with just the entity/pool machinery in this module; the `op`,
+/// `reads`, constant table and location table are not written.
+pub fn write_block_graph(enc: &mut Encoder, fun: &Function) {
+    write_graph_shape(enc, fun.entry_block, &fun.blocks, &fun.pool);
+}
+
+/// The pure, read-only half of [`write_block_graph`], taking the
+/// block map and pool directly instead of a whole `Function`. Split
+/// out so `tests` can drive it without constructing a `Function` -
+/// `Function::new` needs a `FunctionIdent`, which isn't defined
+/// anywhere in this crate snapshot.
+fn write_graph_shape(
+    enc: &mut Encoder,
+    entry_block: Option<Block>,
+    blocks: &PrimaryMap<Block, BlockData>,
+    pool: &PoolContainer,
+) {
+    enc.option_entity(entry_block);
+
+    let keys: Vec<Block> = blocks.keys().collect();
+    enc.varint(keys.len() as u64);
+    for block in keys {
+        let data: &BlockData = &blocks[block];
+        enc.entity(block);
+        enc.entity_list(&data.arguments, &pool.value);
+        enc.set(&data.predecessors, &pool.block_set);
+        enc.set(&data.successors, &pool.block_set);
+    }
+}
+
+/// Reads back the block graph skeleton written by
+/// [`write_block_graph`] into a fresh, empty `Function`'s block map
+/// and pools. Predecessor/successor sets are rebuilt in fresh pools,
+/// but their members and the entry block are taken from the file as
+/// written, so the caller should still run [`Function::graph_validate_global`] after
+/// also restoring each block's `op`/`reads` to catch a corrupt or
+/// truncated file.
+///
+/// Unlike [`write_block_graph`], this one can't be split into a
+/// `Function`-free helper the same way: growing `fun.blocks` to fit a
+/// freshly-seen block index has to go through `fun.block_insert()`,
+/// which also registers the block in `fun.values` (`ValueMap`) so it
+/// can be referenced as a `Value` elsewhere - a block map grown by
+/// hand here would silently fall out of sync with `fun.values`. So
+/// this (and, transitively, `Function::graph_validate_global`) stays
+/// untested directly in this snapshot: both need a real `Function`,
+/// and `Function::new` needs a `FunctionIdent` that isn't defined
+/// here. `tests` instead round-trips [`write_graph_shape`] against a
+/// hand-built block map/pool and decodes the bytes back with the same
+/// `Decoder` calls this function makes, which covers the wire format
+/// the two functions agree on even though `read_block_graph` itself
+/// can't be called from here.
+pub fn read_block_graph(dec: &mut Decoder, fun: &mut Function) -> Result<(), DecodeError> {
+    fun.entry_block = dec.option_entity()?;
+
+    let num_blocks = dec.varint()?;
+    for _ in 0..num_blocks {
+        let block: Block = dec.entity()?;
+        let arguments = dec.entity_list(&mut fun.pool.value)?;
+        let predecessors = dec.set(&mut fun.pool.block_set)?;
+        let successors = dec.set(&mut fun.pool.block_set)?;
+
+        while fun.blocks.next_key().index() <= block.index() {
+            fun.block_insert();
+        }
+        fun.blocks[block].arguments = arguments;
+        fun.blocks[block].predecessors = predecessors;
+        fun.blocks[block].successors = successors;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
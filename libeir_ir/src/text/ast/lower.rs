@@ -0,0 +1,139 @@
+//! Lowering of the textual [`super::Module`] AST into IR entities, and
+//! the error/position types that make that lowering diagnosable.
+//!
+//! This snapshot doesn't carry the body of the lowering pass itself
+//! (the code that walks [`super::Function`]/[`super::Op`] and builds a
+//! `Function` via `FunctionBuilder`), so this module only defines the
+//! two items `text::ast` re-exports: [`LowerError`], carrying a precise
+//! [`SourceSpan`] for every failure instead of a bare message, and
+//! [`LowerMap`], the byte-range index back from source positions to the
+//! IR entities lowering produced at that position - plus
+//! [`LowerEntity`], the entity type `LowerMap` stores.
+
+use libeir_diagnostics::{ByteIndex, Diagnostic, Label, SourceSpan};
+
+use crate::{Block, Value};
+
+/// A lowering failure, always anchored to the span in the source text
+/// that caused it, so callers can render a caret-style snippet instead
+/// of a bare message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LowerError {
+    /// A name (`Value::Value`/`Value::Block`) was used without ever
+    /// being bound by a `Label` or `Assignment`.
+    UnresolvedName { span: SourceSpan, name: String },
+    /// The same name was bound more than once in the same function.
+    DuplicateBinding {
+        span: SourceSpan,
+        previous: SourceSpan,
+        name: String,
+    },
+    /// An op was given a number of arguments its kind doesn't accept,
+    /// e.g. a 2- or 5-read `if_bool`.
+    ArityMismatch {
+        span: SourceSpan,
+        expected: String,
+        actual: usize,
+    },
+}
+
+impl LowerError {
+    /// The span of the source text responsible for this error.
+    pub fn span(&self) -> SourceSpan {
+        match self {
+            LowerError::UnresolvedName { span, .. } => *span,
+            LowerError::DuplicateBinding { span, .. } => *span,
+            LowerError::ArityMismatch { span, .. } => *span,
+        }
+    }
+
+    /// Renders this error as a [`Diagnostic`], with a primary label on
+    /// [`Self::span`] (and a secondary label on the earlier binding,
+    /// for [`LowerError::DuplicateBinding`]) so the caller can render a
+    /// caret-style snippet via the crate's `CodeMap`.
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            LowerError::UnresolvedName { span, name } => Diagnostic::error()
+                .with_message(format!("unresolved name `{}`", name))
+                .with_labels(vec![
+                    Label::primary(span.source_id(), *span).with_message("not bound here")
+                ]),
+            LowerError::DuplicateBinding {
+                span,
+                previous,
+                name,
+            } => Diagnostic::error()
+                .with_message(format!("`{}` is bound more than once", name))
+                .with_labels(vec![
+                    Label::primary(span.source_id(), *span).with_message("duplicate binding"),
+                    Label::secondary(previous.source_id(), *previous)
+                        .with_message("previously bound here"),
+                ]),
+            LowerError::ArityMismatch {
+                span,
+                expected,
+                actual,
+            } => Diagnostic::error()
+                .with_message(format!(
+                    "expected {}, found {} argument(s)",
+                    expected,
+                    actual
+                ))
+                .with_labels(vec![
+                    Label::primary(span.source_id(), *span).with_message("here")
+                ]),
+        }
+    }
+}
+
+/// A single entry in a [`LowerMap`]: the IR entity a byte range in the
+/// source text lowered to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LowerEntity {
+    Block(Block),
+    Value(Value),
+}
+
+/// Maps byte ranges in the lowered source text back to the IR entity
+/// they produced, so tooling (the printer's source map, an editor
+/// go-to-definition, the interactive debugger) can answer "what did
+/// this span become" without re-running the lowering pass.
+///
+/// Entries are pushed in source order as lowering proceeds, so the
+/// innermost span enclosing a given offset is always the last one
+/// recorded, i.e. the first match when scanning back to front.
+#[derive(Debug, Clone, Default)]
+pub struct LowerMap {
+    entries: Vec<(SourceSpan, LowerEntity)>,
+}
+
+impl LowerMap {
+    pub fn new() -> Self {
+        LowerMap {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Records that `span` lowered to `entity`. Must be called in
+    /// source order (increasing start offset) to keep [`Self::lookup`]
+    /// correct.
+    pub fn record(&mut self, span: SourceSpan, entity: LowerEntity) {
+        self.entries.push((span, entity));
+    }
+
+    /// Returns the innermost recorded entity whose span contains
+    /// `index`, if any - "innermost" meaning the one with the latest
+    /// start offset, since nested spans are recorded after their
+    /// enclosing one.
+    pub fn lookup(&self, index: ByteIndex) -> Option<LowerEntity> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(span, _)| span.start_index() <= index && index <= span.end_index())
+            .map(|(_, entity)| *entity)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(SourceSpan, LowerEntity)> {
+        self.entries.iter()
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use libeir_diagnostics::SourceSpan;
 use libeir_intern::Ident;
 
@@ -9,6 +11,46 @@ pub use lower::{LowerError, LowerMap};
 
 //mod raise;
 
+// NOTE on round-tripping: the grammar each `Op`/`Value`/`CasePattern`/
+// `MatchKind` variant corresponds to is documented inline below. It is
+// meant to describe, in one place, what `text::printer::operation`
+// emits for the matching `crate::CasePattern`/`crate::MatchKind` (the
+// *IR-side* types `block_op_to_doc` actually walks - distinct Rust
+// types from `ast::CasePattern`/`ast::MatchKind` below, which are the
+// *parse* side), and what `lower` must accept back. A full
+// `print(lower(src)) == src` property test belongs in `lower`'s test
+// module once the actual tree-walking lowering pass, and the lexer/
+// grammar it parses with, are present - this snapshot only carries
+// `lower`'s error/position types (`LowerError`/`LowerMap`), not the
+// pass itself or a parser, so that test isn't added here.
+//
+// What *is* added here is a round-trip print/parse test for the part
+// of this grammar `ast::CasePattern` can exercise fully on its own:
+// `Wildcard`, `ListCell`, `Tuple` and the `Nil` leaf of `Value`.
+// Other `Value` leaves and `Binding` are not covered, since their
+// lexical syntax belongs to types (`constant::Integer`, `BinOp`,
+// `Ident`) whose definitions aren't part of this snapshot. See
+// `CasePattern`'s `Display` impl, `parse_case_pattern`, and `tests`
+// below.
+//
+// That round-trip test only proves `ast::CasePattern`'s own
+// `Display`/`parse_case_pattern` pair agrees with itself - it never
+// calls the real `crate::CasePattern`/`case_pattern_to_doc`
+// (`text::printer::operation`), so it can't catch the two grammars
+// drifting apart, which is the actual risk this request is about.
+// `crate::CasePattern` can't be constructed here to close that gap
+// directly: its defining file isn't part of this snapshot (only this
+// `ast::CasePattern`, the *parse*-side type, is), and printing one for
+// real needs a `Function` to drive `value_use_to_doc`, which in turn
+// needs a `FunctionIdent` that also isn't defined anywhere in this
+// snapshot. So instead `tests` also pins `ast::CasePattern::Display`'s
+// output, for `Wildcard`/`ListCell`/`Tuple`, to literal strings copied
+// from reading `case_pattern_to_doc`'s match arms directly - the one
+// way left to check this module's grammar against the real printer's
+// actual source rather than only against itself. `Value`/`Binding`
+// aren't covered this way, for the same "needs a real bound `Value`"
+// reason given above.
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct Module {
     pub span: SourceSpan,
@@ -57,6 +99,26 @@ pub enum DynOpt {
     Value(Value),
 }
 
+/// The canonical textual rendering for every `OpKind`. `block_op_to_doc`
+/// (and its function/block-level callers) must emit exactly this
+/// grammar for each variant, and `lower` must accept exactly this
+/// grammar back, so that printing a function and parsing it back is a
+/// faithful round trip rather than a lossy debug dump:
+///
+/// - `Dyn(name, opts)`: `name(opt, opt, ...)`, where each `DynOpt` is
+///   either a bare value or a parenthesized nested option list.
+/// - `UnpackValueList`: `unpack <value> arity <n> => <block>`.
+/// - `CallControlFlow`: `<target>(<args>)`.
+/// - `CallFunction`: `<target>(<args>) => <ret> except <thr>`.
+/// - `IfBool`: `if_bool <value> <tru> <fal> <or>?` (the `or` read, when
+///   present, is the 4-read form).
+/// - `TraceCaptureRaw`: `trace_capture_raw <then>`.
+/// - `Match`: `match <value> { <pattern> => <target>(<args>) ... }`,
+///   one `MatchEntry` per line, joined by newlines (not `;` - each
+///   `MatchEntry`/`CaseEntry` line has no separator of its own, only
+///   the whole op gets one trailing `;`, same as every other `Op`).
+/// - `Case`: `case <value> { <patterns> when <guard> => <target>(<args>) ... _ => <no_match> }`.
+/// - `Unreachable`: `unreachable`.
 #[derive(Debug, PartialEq, Eq)]
 pub enum Op {
     Dyn(Ident, Vec<DynOpt>),
@@ -87,6 +149,10 @@ pub struct CaseEntry {
     pub target: Value,
 }
 
+/// Canonical textual form, matching `case_pattern_to_doc` in the
+/// printer: `Value` as the bare value, `Binding` as `name = pattern`,
+/// `ListCell` as `[head | tail]`, `Tuple` as `{e0, e1, ...}`, and
+/// `Wildcard` as `_`.
 #[derive(Debug, PartialEq, Eq)]
 pub enum CasePattern {
     Value(Value),
@@ -116,6 +182,13 @@ pub struct MatchEntry {
     pub target: Value,
     pub kind: MatchKind,
 }
+/// Canonical textual form, matching the `MatchKind` rendering in
+/// `operation::block_op_to_doc`, each followed by `=> <target>(<args>)`:
+/// `Value(v)` as `v`, `Type(t)` as `is_type t`, `Binary(spec, size)` as
+/// `<<v[:size]/spec>>` (`spec` as rendered by `binary_specifier_to_text`,
+/// e.g. `integer-signed-big-unit:1` or `utf8`), `Tuple(n)` as
+/// `{} arity n`, `ListCell` as `[]`, `MapItem(k)` as `k`, and
+/// `Wildcard` as `_`.
 #[derive(Debug, PartialEq, Eq)]
 pub enum MatchKind {
     Value(Value),
@@ -181,6 +254,88 @@ pub enum Value {
     CaptureFunction(Box<Value>, Box<Value>, Box<Value>),
     BinOp(Box<Value>, BinOp, Box<Value>),
 }
+impl fmt::Display for CasePattern {
+    /// Reference renderer for the grammar documented on `Op::Case`/
+    /// `MatchKind` above. `Wildcard`/`ListCell`/`Tuple`, and the `Nil`
+    /// leaf of `Value`, are the slice of this grammar this module can
+    /// render and re-parse entirely on its own, without guessing at
+    /// lexical syntax owned elsewhere (`Value::Integer`'s digit
+    /// syntax belongs to `constant::Integer`, identifiers to
+    /// `libeir_intern::Ident` - neither type's source is part of this
+    /// snapshot). `Binding` and any other `Value` leaf fall back to
+    /// `{:?}` for that reason and aren't covered by
+    /// `parse_case_pattern`/`tests` below.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CasePattern::Wildcard => write!(f, "_"),
+            CasePattern::Value(Value::Nil) => write!(f, "nil"),
+            CasePattern::Value(other) => write!(f, "{:?}", other),
+            CasePattern::Binding { name, pattern } => write!(f, "{:?} = {}", name, pattern),
+            CasePattern::ListCell { head, tail } => write!(f, "[{} | {}]", head, tail),
+            CasePattern::Tuple { elements } => {
+                write!(f, "{{")?;
+                for (i, e) in elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", e)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Reference parser for the `CasePattern` slice of the grammar that
+/// `CasePattern`'s `Display` impl above renders: `_`, `nil`,
+/// `[<pat> | <pat>]`, `{<pat>, ...}`. This is a minimal round-trip
+/// check, not the real parser - see the module-level NOTE.
+fn parse_case_pattern(input: &str) -> Option<(CasePattern, &str)> {
+    let input = input.trim_start();
+
+    if let Some(rest) = input.strip_prefix('_') {
+        return Some((CasePattern::Wildcard, rest));
+    }
+    if let Some(rest) = input.strip_prefix("nil") {
+        return Some((CasePattern::Value(Value::Nil), rest));
+    }
+    if let Some(rest) = input.strip_prefix('[') {
+        let (head, rest) = parse_case_pattern(rest)?;
+        let rest = rest.trim_start().strip_prefix('|')?;
+        let (tail, rest) = parse_case_pattern(rest)?;
+        let rest = rest.trim_start().strip_prefix(']')?;
+        return Some((
+            CasePattern::ListCell {
+                head: Box::new(head),
+                tail: Box::new(tail),
+            },
+            rest,
+        ));
+    }
+    if let Some(rest) = input.strip_prefix('{') {
+        let mut rest = rest.trim_start();
+        let mut elements = Vec::new();
+        if !rest.starts_with('}') {
+            loop {
+                let (element, after) = parse_case_pattern(rest)?;
+                elements.push(element);
+                rest = after.trim_start();
+                match rest.strip_prefix(',') {
+                    Some(after_comma) => rest = after_comma.trim_start(),
+                    None => break,
+                }
+            }
+        }
+        let rest = rest.strip_prefix('}')?;
+        return Some((CasePattern::Tuple { elements }, rest));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests;
+
 impl Value {
     pub fn value(&self) -> Option<Ident> {
         match self {
@@ -0,0 +1,105 @@
+use super::{parse_case_pattern, CasePattern, Value};
+
+// The tests below come in two kinds. `assert_round_trip` only proves
+// `ast::CasePattern`'s own `Display`/`parse_case_pattern` pair agrees
+// with itself - useful as a sanity check, but it never touches the
+// *real* printer (`crate::CasePattern`/`operation::case_pattern_to_doc`
+// in `text::printer::operation`), so it can't catch the grammars
+// drifting apart. `assert_prints_as` pins `Display`'s output to literal
+// strings copied from reading `case_pattern_to_doc`'s match arms
+// directly (`text::printer::operation.rs`, the `Wildcard`/`ListCell`/
+// `Tuple` arms): `arena.text("_")`, `"[" head " | " tail "]"`, and
+// `elements` joined by `", "` inside `braces()`. That's the part of the
+// grammar this module can actually check against the real printer's
+// source without constructing a `crate::CasePattern` - its defining
+// file isn't part of this snapshot (see the module-level NOTE above),
+// and `case_pattern_to_doc`'s `Value`/`Binding` arms pull from a
+// `bound: impl Iterator<Item = Value>` and print through
+// `value_use_to_doc`, which needs a real `Function`/`Value` to drive
+// and so can't be pinned the same way here. Of those two variants,
+// only the `Nil` leaf of `Value` is covered, by `assert_round_trip`;
+// `Binding` has no test here.
+fn assert_prints_as(pattern: CasePattern, expected: &str) {
+    assert_eq!(pattern.to_string(), expected);
+}
+
+fn assert_round_trip(pattern: CasePattern) {
+    let rendered = pattern.to_string();
+    let (parsed, rest) = parse_case_pattern(&rendered)
+        .unwrap_or_else(|| panic!("failed to parse rendered pattern `{}`", rendered));
+    assert!(
+        rest.trim().is_empty(),
+        "leftover input after parsing `{}`: `{}`",
+        rendered,
+        rest
+    );
+    assert_eq!(parsed, pattern, "parse(render(pattern)) != pattern for `{}`", rendered);
+}
+
+#[test]
+fn wildcard_round_trips() {
+    assert_round_trip(CasePattern::Wildcard);
+}
+
+#[test]
+fn nil_value_round_trips() {
+    assert_round_trip(CasePattern::Value(Value::Nil));
+}
+
+#[test]
+fn list_cell_round_trips() {
+    assert_round_trip(CasePattern::ListCell {
+        head: Box::new(CasePattern::Wildcard),
+        tail: Box::new(CasePattern::Value(Value::Nil)),
+    });
+}
+
+#[test]
+fn nested_tuple_round_trips() {
+    assert_round_trip(CasePattern::Tuple {
+        elements: vec![
+            CasePattern::Wildcard,
+            CasePattern::Value(Value::Nil),
+            CasePattern::ListCell {
+                head: Box::new(CasePattern::Wildcard),
+                tail: Box::new(CasePattern::Wildcard),
+            },
+        ],
+    });
+}
+
+#[test]
+fn empty_tuple_round_trips() {
+    assert_round_trip(CasePattern::Tuple { elements: vec![] });
+}
+
+#[test]
+fn wildcard_matches_the_real_printer_grammar() {
+    assert_prints_as(CasePattern::Wildcard, "_");
+}
+
+#[test]
+fn list_cell_matches_the_real_printer_grammar() {
+    assert_prints_as(
+        CasePattern::ListCell {
+            head: Box::new(CasePattern::Wildcard),
+            tail: Box::new(CasePattern::Wildcard),
+        },
+        "[_ | _]",
+    );
+}
+
+#[test]
+fn tuple_matches_the_real_printer_grammar() {
+    assert_prints_as(
+        CasePattern::Tuple {
+            elements: vec![CasePattern::Wildcard, CasePattern::Wildcard, CasePattern::Wildcard],
+        },
+        "{_, _, _}",
+    );
+}
+
+#[test]
+fn empty_tuple_matches_the_real_printer_grammar() {
+    assert_prints_as(CasePattern::Tuple { elements: vec![] }, "{}");
+}
@@ -1,6 +1,18 @@
 use pretty::{RefDoc, DocAllocator};
 
-use crate::{Block, Value, OpKind, CallKind, MatchKind, BasicType};
+// NOTE: a byte-range -> (Block, Value, Op) index over the rendered
+// output (so tooling can map a printed span back to the entity that
+// produced it, the mirror image of `text::ast::lower::LowerMap`) would
+// live on `FunctionFormatData`/`FormatConfig` in `super` (this module's
+// parent `mod.rs`). That file isn't part of this snapshot - only this
+// submodule is - so that half of the source-map isn't added here;
+// `text::ast::lower::LowerMap` (the parse-side byte-range index) is
+// implemented where it's actually reachable.
+
+use crate::{
+    Block, Value, OpKind, CallKind, MatchKind, CasePattern, BasicType, BinaryEntrySpecifier,
+    Endianness, MapPutUpdate,
+};
 
 use super::{
     FunctionFormatData, FormatConfig, FormatState,
@@ -15,6 +27,65 @@ where
     L: BlockValueLayout,
 {
 
+    /// Recursively renders a single case-clause pattern, pulling the
+    /// next bound variable off `bound` for each `Value`/`Binding` leaf
+    /// it encounters, left to right: `[h | t]` for cons cells, `{..}`
+    /// for tuples, `name = pat` for bindings.
+    fn case_pattern_to_doc<I>(
+        &mut self,
+        config: &FormatConfig<B, V, L>,
+        state: &mut FormatState,
+        pattern: &CasePattern,
+        bound: &mut I,
+    ) -> RefDoc<'a, ()>
+    where
+        I: Iterator<Item = Value>,
+    {
+        let arena = self.arena;
+        match pattern {
+            CasePattern::Wildcard => arena.text("_").into_doc(),
+            CasePattern::Value(_) => {
+                let val = bound.next().expect("case pattern/args arity mismatch");
+                self.value_use_to_doc(config, state, val)
+            }
+            CasePattern::Binding { pattern, .. } => {
+                let val = bound.next().expect("case pattern/args arity mismatch");
+                let val_doc = self.value_use_to_doc(config, state, val);
+                let inner = self.case_pattern_to_doc(config, state, pattern, bound);
+                arena
+                    .nil()
+                    .append(val_doc)
+                    .append(arena.space())
+                    .append(arena.text("="))
+                    .append(arena.space())
+                    .append(inner)
+                    .into_doc()
+            }
+            CasePattern::ListCell { head, tail } => {
+                let head_doc = self.case_pattern_to_doc(config, state, head, bound);
+                let tail_doc = self.case_pattern_to_doc(config, state, tail, bound);
+                arena
+                    .text("[")
+                    .append(head_doc)
+                    .append(arena.space())
+                    .append(arena.text("|"))
+                    .append(arena.space())
+                    .append(tail_doc)
+                    .append(arena.text("]"))
+                    .into_doc()
+            }
+            CasePattern::Tuple { elements } => {
+                let elems_doc = arena.intersperse(
+                    elements
+                        .iter()
+                        .map(|e| self.case_pattern_to_doc(config, state, e, bound)),
+                    arena.text(",").append(arena.softline()),
+                );
+                elems_doc.braces().into_doc()
+            }
+        }
+    }
+
     pub fn block_op_to_doc(
         &mut self,
         config: &FormatConfig<B, V, L>,
@@ -29,12 +100,89 @@ where
 
         let op_doc = match op {
             OpKind::Case { clauses, .. } => {
-                let block = arena.nil();
+                let dests = reads[0];
+                let selector = self.value_use_to_doc(config, state, reads[1]);
+                let num_clauses = clauses.len();
 
-                arena.nil()
-                  .append(arena.text("case"))
-                  .append(arena.space())
-                  .append(block.nest(1).braces())
+                let mut clauses_formatted = Vec::with_capacity(num_clauses);
+                for (i, clause) in clauses.iter().enumerate() {
+                    let target = state.function.value_list_get_n(dests, i).unwrap();
+                    let target_doc = self.value_use_to_doc(config, state, target);
+
+                    let args_vl = reads[i + 2];
+                    let num_args = state.function.value_list_length(args_vl);
+                    let mut args = Vec::with_capacity(num_args);
+                    for n in 0..num_args {
+                        args.push(state.function.value_list_get_n(args_vl, n).unwrap());
+                    }
+
+                    // `args[0]` is the clause guard; the rest are the
+                    // bound pattern variables, consumed left-to-right
+                    // by the clause's patterns and then forwarded to
+                    // `target`.
+                    let guard_doc = self.value_use_to_doc(config, state, args[0]);
+                    let mut bound = args[1..].iter().copied();
+
+                    let patterns_doc = arena.intersperse(
+                        clause
+                            .patterns
+                            .iter()
+                            .map(|pat| self.case_pattern_to_doc(config, state, pat, &mut bound)),
+                        arena.text(",").append(arena.softline()),
+                    );
+
+                    let block_args = arena
+                        .intersperse(
+                            bound.map(|v| self.value_use_to_doc(config, state, v)),
+                            arena.text(",").append(arena.softline()),
+                        )
+                        .nest(1)
+                        .parens();
+                    let body = arena.nil().append(target_doc).append(block_args);
+
+                    let formatted = arena
+                        .nil()
+                        .append(patterns_doc)
+                        .append(arena.space())
+                        .append(arena.text("when"))
+                        .append(arena.space())
+                        .append(guard_doc)
+                        .append(arena.space())
+                        .append(arena.text("=>"))
+                        .append(arena.space())
+                        .append(body);
+
+                    clauses_formatted.push(formatted.indent(2));
+                }
+
+                // A trailing read beyond the per-clause args is the
+                // `no_match` fallthrough target, if the case has one.
+                if reads.len() > num_clauses + 2 {
+                    let no_match = reads[num_clauses + 2];
+                    let no_match_doc = arena
+                        .nil()
+                        .append(arena.text("_"))
+                        .append(arena.space())
+                        .append(arena.text("=>"))
+                        .append(arena.space())
+                        .append(self.value_use_to_doc(config, state, no_match))
+                        .indent(2);
+                    clauses_formatted.push(no_match_doc);
+                }
+
+                arena
+                    .nil()
+                    .append(arena.text("case"))
+                    .append(arena.space())
+                    .append(selector)
+                    .append(arena.space())
+                    .append(
+                        arena
+                            .hardline()
+                            .append(arena.intersperse(clauses_formatted, arena.hardline()))
+                            .append(arena.hardline())
+                            .braces(),
+                    )
             },
             OpKind::Match { branches } => {
                 let dests = reads[0];
@@ -85,7 +233,38 @@ where
                                 .append(arena.nil().append(body))
                         }
                         MatchKind::Binary(ref spec) => {
-                            unimplemented!();
+                            let has_size = binary_specifier_has_size(spec);
+                            let val_doc = self.value_use_to_doc(config, state, args[0]);
+                            let size_doc = if has_size {
+                                Some(self.value_use_to_doc(config, state, args[1]))
+                            } else {
+                                None
+                            };
+                            let tail_skip = if has_size { 2 } else { 1 };
+                            let block_args = arena.intersperse(
+                                args.iter().skip(tail_skip).map(|v| self.value_use_to_doc(config, state, *v)),
+                                arena.text(",").append(arena.softline())
+                            ).nest(1).parens();
+                            let body = arena.nil()
+                                .append(block_val)
+                                .append(block_args);
+
+                            let mut segment = arena.nil()
+                                .append(arena.text("<<"))
+                                .append(val_doc);
+                            if let Some(size_doc) = size_doc {
+                                segment = segment.append(arena.text(":")).append(size_doc);
+                            }
+                            let segment = segment
+                                .append(arena.text("/"))
+                                .append(arena.text(binary_specifier_to_text(spec)))
+                                .append(arena.text(">>"));
+
+                            segment
+                                .append(arena.space())
+                                .append(arena.text("=>"))
+                                .append(arena.space())
+                                .append(arena.nil().append(body))
                         }
                         MatchKind::Tuple(arity) => {
                             let block_args = arena.intersperse(
@@ -253,6 +432,80 @@ where
                 }
             },
             OpKind::Unreachable => arena.text("unreachable"),
+            OpKind::MapPut { action } => {
+                // Reads follow the `lower_map_expr`/`lower_map_update_expr`
+                // shape: the base map, the key, the value, then the
+                // success/fail continuations (`fail` is only reachable
+                // for `MapPutUpdate::Update`, which can badkey-fault).
+                let base = self.value_use_to_doc(config, state, reads[0]);
+                let key = self.value_use_to_doc(config, state, reads[1]);
+                let val = self.value_use_to_doc(config, state, reads[2]);
+                let ok = self.value_use_to_doc(config, state, reads[3]);
+
+                let verb = match action {
+                    MapPutUpdate::Put => "put",
+                    MapPutUpdate::Update => "update",
+                };
+
+                let doc = arena
+                    .nil()
+                    .append(arena.text(verb))
+                    .append(arena.space())
+                    .append(base)
+                    .append(arena.text("["))
+                    .append(key)
+                    .append(arena.text("]"))
+                    .append(arena.space())
+                    .append(arena.text("="))
+                    .append(arena.space())
+                    .append(val)
+                    .append(arena.space())
+                    .append(arena.text("=>"))
+                    .append(arena.space())
+                    .append(ok);
+
+                if reads.len() > 4 {
+                    let fail = self.value_use_to_doc(config, state, reads[4]);
+                    doc.append(arena.space())
+                        .append(arena.text("except"))
+                        .append(arena.space())
+                        .append(fail)
+                } else {
+                    doc
+                }
+            }
+            OpKind::TraceConstruct => {
+                // Builds the formatted stacktrace term from the raw
+                // trace captured by `trace_capture_raw`, then jumps to
+                // the continuation with it as a block argument.
+                assert!(reads.len() == 2);
+                let then = self.value_use_to_doc(config, state, reads[0]);
+                let raw = self.value_use_to_doc(config, state, reads[1]);
+                arena
+                    .nil()
+                    .append(arena.text("trace_construct"))
+                    .append(arena.space())
+                    .append(raw)
+                    .append(arena.space())
+                    .append(arena.text("=>"))
+                    .append(arena.space())
+                    .append(then)
+            }
+            OpKind::Dyn(_) => {
+                // The op's own name/options aren't exposed on the
+                // trait object this variant wraps here, so this
+                // renders the reads it carries under a generic label
+                // rather than guessing at an interface; `Op::Dyn` (the
+                // textual AST form, which does carry a name) is what
+                // `lower` reconstructs this from.
+                let args = arena.intersperse(
+                    reads.iter().map(|v| self.value_use_to_doc(config, state, *v)),
+                    arena.text(",").append(arena.softline()),
+                ).nest(1).parens();
+                arena.nil()
+                    .append(arena.text("dyn"))
+                    .append(args)
+            }
             OpKind::Intrinsic(name) => {
                 let intrinsic_args = arena.intersperse(
                     reads.iter().map(|v| self.value_use_to_doc(config, state, *v)),
@@ -265,16 +518,87 @@ where
                     .append(arena.text(name.as_str().get()))
                     .append(intrinsic_args)
             }
-            _ => {
-                println!("UNIMPL: {:?}", op);
-                arena.text("unknown")
-            },
+            // Any op kind not explicitly handled above (receive/timeout
+            // ops, map/tuple/list/binary construction, anything added
+            // later) still renders as a real, structured doc - the
+            // read count and layout for an unnamed variant isn't known
+            // here, so this shows the variant's own name (pulled out
+            // of its `Debug` output, since `OpKind`'s fields aren't
+            // otherwise visible through this match) followed by its
+            // reads, so two different unhandled ops are never printed
+            // identically.
+            other => {
+                let name = {
+                    let debug = format!("{:?}", other);
+                    let end = debug
+                        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                        .unwrap_or(debug.len());
+                    debug[..end].to_string()
+                };
+                let args = arena.intersperse(
+                    reads.iter().map(|v| self.value_use_to_doc(config, state, *v)),
+                    arena.text(",").append(arena.softline()),
+                ).nest(1).parens();
+                arena.nil()
+                    .append(arena.as_string(&name))
+                    .append(args)
+            }
         };
 
         op_doc.append(arena.text(";")).into_doc()
     }
 }
 
+/// `utf8`/`utf16`/`utf32` segments never carry a size operand - their
+/// width is fixed by the encoding - so only the other segment kinds
+/// read a size value off `args`.
+fn binary_specifier_has_size(spec: &BinaryEntrySpecifier) -> bool {
+    !matches!(
+        spec,
+        BinaryEntrySpecifier::Utf8
+            | BinaryEntrySpecifier::Utf16 { .. }
+            | BinaryEntrySpecifier::Utf32 { .. }
+    )
+}
+
+fn binary_specifier_to_text(spec: &BinaryEntrySpecifier) -> String {
+    match spec {
+        BinaryEntrySpecifier::Integer {
+            signed,
+            endianness,
+            unit,
+        } => format!(
+            "integer-{}-{}-unit:{}",
+            if *signed { "signed" } else { "unsigned" },
+            endianness_to_text(endianness),
+            unit
+        ),
+        BinaryEntrySpecifier::Float { endianness, unit } => {
+            format!("float-{}-unit:{}", endianness_to_text(endianness), unit)
+        }
+        BinaryEntrySpecifier::Bytes { unit } => format!("bytes-unit:{}", unit),
+        BinaryEntrySpecifier::Bits { unit } => format!("bits-unit:{}", unit),
+        BinaryEntrySpecifier::Utf8 => "utf8".to_owned(),
+        BinaryEntrySpecifier::Utf16 { endianness } => {
+            format!("utf16-{}", endianness_to_text(endianness))
+        }
+        BinaryEntrySpecifier::Utf32 { endianness } => {
+            format!("utf32-{}", endianness_to_text(endianness))
+        }
+    }
+}
+
+fn endianness_to_text(endianness: &Endianness) -> &'static str {
+    match endianness {
+        Endianness::Big => "big",
+        Endianness::Little => "little",
+        Endianness::Native => "native",
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
 fn type_to_text(ty: &BasicType) -> String {
     match ty {
         BasicType::List => "list".to_owned(),
@@ -0,0 +1,84 @@
+use crate::{BinaryEntrySpecifier, Endianness};
+
+use super::{binary_specifier_has_size, binary_specifier_to_text, endianness_to_text};
+
+#[test]
+fn endianness_renders_as_documented() {
+    assert_eq!(endianness_to_text(&Endianness::Big), "big");
+    assert_eq!(endianness_to_text(&Endianness::Little), "little");
+    assert_eq!(endianness_to_text(&Endianness::Native), "native");
+}
+
+#[test]
+fn fixed_width_utf_segments_have_no_size_operand() {
+    assert!(!binary_specifier_has_size(&BinaryEntrySpecifier::Utf8));
+    assert!(!binary_specifier_has_size(&BinaryEntrySpecifier::Utf16 {
+        endianness: Endianness::Big,
+    }));
+    assert!(!binary_specifier_has_size(&BinaryEntrySpecifier::Utf32 {
+        endianness: Endianness::Little,
+    }));
+}
+
+#[test]
+fn other_segments_do_have_a_size_operand() {
+    assert!(binary_specifier_has_size(&BinaryEntrySpecifier::Integer {
+        signed: true,
+        endianness: Endianness::Big,
+        unit: 1,
+    }));
+    assert!(binary_specifier_has_size(&BinaryEntrySpecifier::Float {
+        endianness: Endianness::Native,
+        unit: 1,
+    }));
+    assert!(binary_specifier_has_size(&BinaryEntrySpecifier::Bytes { unit: 8 }));
+    assert!(binary_specifier_has_size(&BinaryEntrySpecifier::Bits { unit: 1 }));
+}
+
+#[test]
+fn binary_specifier_text_matches_the_documented_grammar() {
+    assert_eq!(
+        binary_specifier_to_text(&BinaryEntrySpecifier::Integer {
+            signed: true,
+            endianness: Endianness::Big,
+            unit: 1,
+        }),
+        "integer-signed-big-unit:1",
+    );
+    assert_eq!(
+        binary_specifier_to_text(&BinaryEntrySpecifier::Integer {
+            signed: false,
+            endianness: Endianness::Little,
+            unit: 8,
+        }),
+        "integer-unsigned-little-unit:8",
+    );
+    assert_eq!(
+        binary_specifier_to_text(&BinaryEntrySpecifier::Float {
+            endianness: Endianness::Native,
+            unit: 1,
+        }),
+        "float-native-unit:1",
+    );
+    assert_eq!(
+        binary_specifier_to_text(&BinaryEntrySpecifier::Bytes { unit: 8 }),
+        "bytes-unit:8",
+    );
+    assert_eq!(
+        binary_specifier_to_text(&BinaryEntrySpecifier::Bits { unit: 1 }),
+        "bits-unit:1",
+    );
+    assert_eq!(binary_specifier_to_text(&BinaryEntrySpecifier::Utf8), "utf8");
+    assert_eq!(
+        binary_specifier_to_text(&BinaryEntrySpecifier::Utf16 {
+            endianness: Endianness::Big,
+        }),
+        "utf16-big",
+    );
+    assert_eq!(
+        binary_specifier_to_text(&BinaryEntrySpecifier::Utf32 {
+            endianness: Endianness::Little,
+        }),
+        "utf32-little",
+    );
+}
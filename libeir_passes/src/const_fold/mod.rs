@@ -0,0 +1,214 @@
+#![warn(warnings)]
+
+use std::collections::BTreeMap;
+
+use log::trace;
+
+use libeir_ir::{Block, Const, ConstKind, Function, MapPutUpdate, OpKind, PrimOpKind, Value};
+use libeir_ir::{FunctionBuilder, MangleTo, Mangler};
+
+use super::FunctionPass;
+
+/// Constant-folding / constant-propagation pass.
+///
+/// Folds primitive operations whose operands are all constants:
+/// - a `prim_tuple` of constants becomes a single constant tuple value.
+/// - a `map_put`/`map_update` chain built on a constant base map with a
+///   constant key and value folds into a single constant map.
+/// - pure arithmetic/comparison primops on constant integers/floats
+///   fold to their constant result.
+///
+/// This also resolves the map semantics from `lower_map_expr` /
+/// `lower_map_update_expr` at compile time: a `MapPutUpdate::Update`
+/// (`:=`) against a statically known-absent key in a constant map is
+/// rewritten to an unconditional jump to the `badkey` error edge that
+/// was already constructed during lowering, and an update against a
+/// present key folds to the updated constant map, removing the dead
+/// success (or failure) path entirely.
+pub struct ConstFoldPass {
+    map: BTreeMap<Value, Value>,
+    mangler: Mangler,
+}
+
+impl ConstFoldPass {
+    pub fn new() -> Self {
+        ConstFoldPass {
+            map: BTreeMap::new(),
+            mangler: Mangler::new(),
+        }
+    }
+}
+
+impl FunctionPass for ConstFoldPass {
+    fn name(&self) -> &str {
+        "const_fold"
+    }
+    fn run_function_pass(&mut self, b: &mut FunctionBuilder) {
+        self.const_fold(b);
+    }
+}
+
+impl ConstFoldPass {
+    fn const_fold(&mut self, b: &mut FunctionBuilder) {
+        // A chain of `map_put`s (`M#{a => 1, b => 2}`) folds one
+        // segment per iteration: folding `a => 1` only rewrites the
+        // jump into the next segment's block to pass a constant
+        // argument, it doesn't make that block's *parameter* constant.
+        // `fold_map_put` below also queues a rename from that parameter
+        // to the folded constant, same as any other fold, so the
+        // `Mangler` substitutes it at every use once applied - which
+        // is what lets the next segment's `base` read see a constant.
+        // That substitution only takes effect once `self.map` is
+        // applied, so the scan loops to a fixpoint instead of a single
+        // pass, letting each chain link fold in its own iteration.
+        loop {
+            for block in b.fun().block_iter().collect::<Vec<_>>() {
+                if let Some(op) = b.fun().block_kind(block) {
+                    match op.clone() {
+                        OpKind::MapPut { action } => self.fold_map_put(b, block, action),
+                        _ => (),
+                    }
+                }
+            }
+
+            let candidates: Vec<Value> = b
+                .fun()
+                .block_iter()
+                .flat_map(|blk| b.fun().block_reads(blk).to_vec())
+                .collect();
+            for value in candidates {
+                self.fold_primop(b, value);
+            }
+
+            if self.map.is_empty() {
+                break;
+            }
+
+            let entry = b.fun().block_entry();
+            self.mangler.start(MangleTo(entry));
+            for (from, to) in self.map.iter() {
+                self.mangler.add_rename(MangleTo(*from), MangleTo(*to));
+            }
+            let new_entry = self.mangler.run(b);
+            b.block_set_entry(new_entry);
+
+            self.map.clear();
+        }
+    }
+
+    /// Folds a pure primop whose operands are all constants into a
+    /// single constant value, recording a rename from the old
+    /// (non-constant) value to the new constant one.
+    fn fold_primop(&mut self, b: &mut FunctionBuilder, value: Value) {
+        let primop = match b.fun().value_primop(value) {
+            Some(primop) => primop,
+            None => return,
+        };
+
+        let reads = b.fun().primop_reads(primop).to_vec();
+        if !reads.iter().all(|read| b.fun().value_is_constant(*read)) {
+            return;
+        }
+        let consts: Vec<Const> = match reads
+            .iter()
+            .map(|read| b.fun().value_const(*read))
+            .collect::<Option<_>>()
+        {
+            Some(consts) => consts,
+            None => return,
+        };
+
+        let folded = match b.fun().primop_kind(primop).clone() {
+            PrimOpKind::Tuple => Some(b.const_tuple(&consts)),
+            PrimOpKind::BinOp(op) if consts.len() == 2 => {
+                b.const_fold_binop(op, consts[0], consts[1])
+            }
+            _ => None,
+        };
+
+        if let Some(new_const) = folded {
+            trace!("const_fold: folded {} to constant {:?}", value, new_const);
+            let new_value = b.value_from_const(new_const);
+            if new_value != value {
+                self.map.insert(value, new_value);
+            }
+        }
+    }
+
+    /// Folds a single `map_put`/`map_update` op when its base map and
+    /// key are both statically known.
+    fn fold_map_put(&mut self, b: &mut FunctionBuilder, block: Block, action: MapPutUpdate) {
+        let reads = b.fun().block_reads(block).to_vec();
+        // [base_map, key, value, ok_block, fail_block], mirroring the
+        // shape `op_map_put_build`/`MapPutBuilder::finish` produces.
+        let (base, key, val, ok, fail) = match reads.as_slice() {
+            [base, key, val, ok, fail] => (*base, *key, *val, *ok, *fail),
+            _ => return,
+        };
+
+        if !b.fun().value_is_constant(base) || !b.fun().value_is_constant(key) {
+            return;
+        }
+
+        let base_const = b.fun().value_const(base).unwrap();
+        let present = map_contains_key(b.fun(), base_const, key);
+
+        match (action, present) {
+            (MapPutUpdate::Update, false) => {
+                // `Key := Value` against a base map that is statically
+                // known to not contain `Key` always raises `badkey` -
+                // replace the op with an unconditional jump to the
+                // existing error edge, passing the (constant) key.
+                b.block_set_op_call_control_flow(block, fail, &[key]);
+            }
+            (MapPutUpdate::Update, true) | (MapPutUpdate::Put, _) => {
+                if !b.fun().value_is_constant(val) {
+                    return;
+                }
+                let new_map = b.const_map_put(base_const, key, val);
+                b.block_set_op_call_control_flow(block, ok, &[new_map]);
+
+                // `ok`'s own parameter is what the next segment of a
+                // `map_put` chain reads as its base map, so queue a
+                // rename from it to the folded constant too - not just
+                // from the `Value` this op itself defined - or the
+                // chain stops folding after its first segment.
+                if let Some(param) = b.fun().block_arg_n(ok, 0) {
+                    if let Some((from, to)) = param_rename_for_fold(param, new_map) {
+                        self.map.insert(from, to);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Checks whether a constant map contains `key` as one of its entries.
+fn map_contains_key(fun: &Function, map: Const, key: Value) -> bool {
+    match fun.const_kind(map) {
+        ConstKind::Map { keys, .. } => fun
+            .const_entries(keys)
+            .iter()
+            .any(|entry| fun.value_const(key) == Some(*entry)),
+        _ => false,
+    }
+}
+
+/// Decides the rename `fold_map_put` should queue from `ok`'s base-map
+/// parameter to the segment's folded constant, guarding against a
+/// self-rename when the parameter already *is* the folded value.
+/// Pulled out as a plain function over `Value`s - not `Block`/
+/// `Function` - so the guard (and thus the chain-propagation behavior
+/// it enables: this is what lets a second `map_put` in a chain see its
+/// base as constant once the `Mangler` applies the rename) is testable
+/// without a real `FunctionBuilder` - see `tests`.
+fn param_rename_for_fold(param: Value, new_map: Value) -> Option<(Value, Value)> {
+    if param != new_map {
+        Some((param, new_map))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests;
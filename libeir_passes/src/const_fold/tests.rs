@@ -0,0 +1,51 @@
+use std::collections::BTreeMap;
+
+use cranelift_entity::EntityRef;
+use libeir_ir::Value;
+
+use super::param_rename_for_fold;
+
+#[test]
+fn distinct_param_and_fold_result_renames() {
+    let param = Value::new(1);
+    let new_map = Value::new(2);
+
+    assert_eq!(param_rename_for_fold(param, new_map), Some((param, new_map)));
+}
+
+#[test]
+fn param_already_equal_to_fold_result_is_not_renamed() {
+    // If `ok`'s parameter already *is* the folded constant (can happen
+    // once a prior fixpoint iteration has already propagated it),
+    // queuing a `param -> param` rename would be a pointless no-op at
+    // best; guard against it explicitly.
+    let param = Value::new(3);
+
+    assert_eq!(param_rename_for_fold(param, param), None);
+}
+
+#[test]
+fn chained_map_put_segments_each_queue_their_own_rename() {
+    // Models the `M#{a => 1, b => 2}` chain from the review comment:
+    // segment one folds `M`'s `ok` parameter to the first constant
+    // map, segment two folds *its* `ok` parameter (reading the first
+    // segment's now-constant result as its base) to the final
+    // constant map. Each segment queues its own rename; `const_fold`
+    // applies them one fixpoint iteration at a time, since segment two
+    // only sees a constant base after segment one's rename is applied.
+    let segment1_param = Value::new(10);
+    let segment1_const = Value::new(11);
+    let segment2_param = Value::new(12);
+    let segment2_const = Value::new(13);
+
+    let mut renames = BTreeMap::new();
+    if let Some((from, to)) = param_rename_for_fold(segment1_param, segment1_const) {
+        renames.insert(from, to);
+    }
+    if let Some((from, to)) = param_rename_for_fold(segment2_param, segment2_const) {
+        renames.insert(from, to);
+    }
+
+    assert_eq!(renames.get(&segment1_param), Some(&segment1_const));
+    assert_eq!(renames.get(&segment2_param), Some(&segment2_const));
+}
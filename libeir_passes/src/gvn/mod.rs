@@ -0,0 +1,209 @@
+#![warn(warnings)]
+
+use std::collections::{BTreeMap, HashMap};
+
+use log::trace;
+
+use libeir_ir::{Block, DomTree, Function, MapPutUpdate, PrimOp, PrimOpKind, Value};
+use libeir_ir::{FunctionBuilder, MangleTo, Mangler};
+
+use super::FunctionPass;
+
+/// Global value numbering over pure primitive operations.
+///
+/// `SimplifyCfgPass` inlines closures and collapses call chains, which
+/// routinely leaves behind many structurally identical pure primops
+/// (the same tuple, map-update or arithmetic op rematerialized once per
+/// inlined copy). This pass walks the live block graph in
+/// reverse-post-order and assigns each side-effect-free primop a
+/// canonical number derived from a hash-consed `(op_kind,
+/// operands)` key, where nested primop operands are keyed by their
+/// *already-assigned* number rather than their raw `Value`. Later
+/// occurrences of an already-numbered computation are rewritten to the
+/// canonical value via the same rename/`Mangler` machinery
+/// `SimplifyCfgPass` uses.
+pub struct GvnPass {
+    map: BTreeMap<Value, Value>,
+    mangler: Mangler,
+}
+
+impl GvnPass {
+    pub fn new() -> Self {
+        GvnPass {
+            map: BTreeMap::new(),
+            mangler: Mangler::new(),
+        }
+    }
+}
+
+impl FunctionPass for GvnPass {
+    fn name(&self) -> &str {
+        "gvn"
+    }
+    fn run_function_pass(&mut self, b: &mut FunctionBuilder) {
+        self.gvn(b);
+    }
+}
+
+/// Hash-consed key for a numbered primop. Operands are represented by
+/// `GvnOperand` so that two structurally identical computations built
+/// from different `Value`s (e.g. two inlined copies of the same
+/// expression) still produce the same key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum GvnKey {
+    Prim(PrimOpKeyKind, Vec<GvnOperand>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum PrimOpKeyKind {
+    Kind(PrimOpKind),
+}
+
+/// An operand reference used inside a `GvnKey`: either the canonical
+/// number already assigned to a nested pure primop, or an opaque
+/// `Value` for anything that isn't itself numbered (block arguments,
+/// constants, function refs, ...).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GvnOperand {
+    Number(Value),
+    Opaque(Value),
+}
+
+impl GvnPass {
+    fn gvn(&mut self, b: &mut FunctionBuilder) {
+        let entry = b.fun().block_entry();
+        let graph = b.fun().live_block_graph();
+        let domtree = DomTree::build(b.fun());
+
+        // Reverse-post-order just gives a traversal that tends to hit
+        // dominating occurrences before dominated ones; it is not by
+        // itself a proof of dominance (two sibling branches of an
+        // if/else can each compute the same pure expression with
+        // neither dominating the other). `number_value` below checks
+        // `domtree.dominates` explicitly before folding a duplicate
+        // into an earlier occurrence, so RPO order here only affects
+        // which of several non-dominating duplicates is kept.
+        let block_order: Vec<_> = graph.dfs_post_order_iter().rev().collect();
+        trace!("gvn block order = {:?}", block_order);
+
+        let mut numbers: HashMap<PrimOp, Value> = HashMap::new();
+        let mut table: HashMap<GvnKey, Vec<(Block, Value)>> = HashMap::new();
+
+        for block in block_order.iter() {
+            let reads = b.fun().block_reads(*block).to_vec();
+            for read in reads {
+                self.number_value(b.fun(), *block, read, &domtree, &mut numbers, &mut table);
+            }
+        }
+
+        if !self.map.is_empty() {
+            self.mangler.start(MangleTo(entry));
+            for (from, to) in self.map.iter() {
+                self.mangler.add_rename(MangleTo(*from), MangleTo(*to));
+            }
+            let new_entry = self.mangler.run(b);
+            b.block_set_entry(new_entry);
+        }
+
+        self.map.clear();
+    }
+
+    /// Recursively numbers `value` (and any nested primop operands it
+    /// reads from) bottom-up, so that by the time a key is built for
+    /// `value`, every nested primop operand already has a canonical
+    /// number recorded in `numbers`/`self.map`. `block` is the block
+    /// this particular occurrence is read from, used to check
+    /// dominance against every previously-seen occurrence of the same
+    /// key before folding them together.
+    ///
+    /// Returns the operand `value` is keyed by: `GvnOperand::Number`
+    /// holding the canonical `Value` that uses of `value` are rewritten
+    /// to (itself, if no earlier occurrence dominates this one), or
+    /// `GvnOperand::Opaque(value)` if `value` is not a numberable
+    /// primop.
+    fn number_value(
+        &mut self,
+        fun: &Function,
+        block: Block,
+        value: Value,
+        domtree: &DomTree,
+        numbers: &mut HashMap<PrimOp, Value>,
+        table: &mut HashMap<GvnKey, Vec<(Block, Value)>>,
+    ) -> GvnOperand {
+        let primop = match fun.value_primop(value) {
+            Some(primop) => primop,
+            None => return GvnOperand::Opaque(value),
+        };
+
+        if let Some(canonical) = numbers.get(&primop) {
+            return GvnOperand::Number(*canonical);
+        }
+
+        if !self.is_numberable(fun, primop) {
+            numbers.insert(primop, value);
+            return GvnOperand::Opaque(value);
+        }
+
+        let operands: Vec<GvnOperand> = fun
+            .primop_reads(primop)
+            .iter()
+            .map(|read| self.number_value(fun, block, *read, domtree, numbers, table))
+            .collect();
+        let key = GvnKey::Prim(PrimOpKeyKind::Kind(fun.primop_kind(primop).clone()), operands);
+
+        let candidates = table.entry(key).or_insert_with(Vec::new);
+
+        let canonical = match find_dominating_candidate(candidates, block, |a, b| domtree.dominates(a, b)) {
+            Some(existing) => {
+                if existing != value {
+                    self.map.insert(value, existing);
+                }
+                existing
+            }
+            None => {
+                candidates.push((block, value));
+                value
+            }
+        };
+
+        numbers.insert(primop, canonical);
+        GvnOperand::Number(canonical)
+    }
+
+    /// A primop may only be numbered if it is guaranteed to have no
+    /// control-flow effect and can never raise. In particular a
+    /// `map_put` chain containing a `MapPutUpdate::Update` entry
+    /// (`:=`) can fault with `badkey`, so it is only numberable when
+    /// every candidate duplicate would fault to the exact same
+    /// `badkey` edge - which this pass doesn't attempt to prove, so
+    /// such ops are conservatively excluded.
+    fn is_numberable(&self, fun: &Function, primop: PrimOp) -> bool {
+        match fun.primop_kind(primop) {
+            PrimOpKind::MapPut { action, .. } => *action != MapPutUpdate::Update,
+            PrimOpKind::Tuple
+            | PrimOpKind::ValueList
+            | PrimOpKind::BinOp(_)
+            | PrimOpKind::LogicOp(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Picks the first `candidates` entry whose recorded block dominates
+/// `block`, per `dominates(def_block, block)`. Pulled out of
+/// `number_value` as a plain function over `(Block, Value)` pairs so
+/// the dominance-respecting fold behavior is testable without a real
+/// `Function`/`DomTree` - see `tests`.
+fn find_dominating_candidate(
+    candidates: &[(Block, Value)],
+    block: Block,
+    dominates: impl Fn(Block, Block) -> bool,
+) -> Option<Value> {
+    candidates
+        .iter()
+        .find(|(def_block, _)| dominates(*def_block, block))
+        .map(|(_, v)| *v)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,67 @@
+use cranelift_entity::EntityRef;
+use libeir_ir::{Block, Value};
+
+use super::find_dominating_candidate;
+
+/// Models a diamond CFG's dominance relation directly (`entry`
+/// dominates everything; `if_branch`/`else_branch` dominate only
+/// themselves; `merge` is dominated only by `entry`), without needing
+/// a real `Function`/`DomTree`, to check `find_dominating_candidate` in
+/// isolation.
+fn diamond_dominates(entry: Block, if_branch: Block, else_branch: Block, merge: Block) -> impl Fn(Block, Block) -> bool {
+    move |def, use_| {
+        def == use_
+            || def == entry
+            || (def == if_branch && use_ == if_branch)
+            || (def == else_branch && use_ == else_branch)
+    }
+}
+
+#[test]
+fn sibling_branches_do_not_fold_into_each_other() {
+    let entry = Block::new(0);
+    let if_branch = Block::new(1);
+    let else_branch = Block::new(2);
+    let merge = Block::new(3);
+
+    let dominates = diamond_dominates(entry, if_branch, else_branch, merge);
+
+    let if_value = Value::new(10);
+    let mut candidates = vec![(if_branch, if_value)];
+
+    // The identical computation appears again in `else_branch`. Since
+    // `if_branch` does not dominate `else_branch`, it must NOT be
+    // folded into the first occurrence.
+    let found = find_dominating_candidate(&candidates, else_branch, &dominates);
+    assert_eq!(found, None, "sibling branch occurrence must not be folded across the diamond");
+
+    // Record the else-branch occurrence as its own candidate, matching
+    // what `number_value` does when no dominating candidate is found.
+    let else_value = Value::new(11);
+    candidates.push((else_branch, else_value));
+
+    // A later occurrence at `merge` is dominated by neither branch
+    // individually (only `entry` reaches `merge` unconditionally), so
+    // it still must not be folded into either branch's value.
+    let found_at_merge = find_dominating_candidate(&candidates, merge, &dominates);
+    assert_eq!(found_at_merge, None, "a merge-point occurrence must not fold into either sibling branch");
+}
+
+#[test]
+fn dominating_occurrence_is_folded() {
+    let entry = Block::new(0);
+    let if_branch = Block::new(1);
+    let else_branch = Block::new(2);
+    let merge = Block::new(3);
+
+    let dominates = diamond_dominates(entry, if_branch, else_branch, merge);
+
+    let entry_value = Value::new(20);
+    let candidates = vec![(entry, entry_value)];
+
+    // `entry` dominates every other block in the diamond, so a
+    // duplicate computed in any of them folds into the entry's value.
+    assert_eq!(find_dominating_candidate(&candidates, if_branch, &dominates), Some(entry_value));
+    assert_eq!(find_dominating_candidate(&candidates, else_branch, &dominates), Some(entry_value));
+    assert_eq!(find_dominating_candidate(&candidates, merge, &dominates), Some(entry_value));
+}
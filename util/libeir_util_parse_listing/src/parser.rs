@@ -1,5 +1,5 @@
 use libeir_util_parse::{Scanner, Parse, Source, SourceError, ErrorReceiver, ToDiagnostic, ArcCodemap};
-use libeir_diagnostics::{Diagnostic, ByteIndex};
+use libeir_diagnostics::{Diagnostic, Label, ByteIndex, SourceSpan};
 
 use super::ast;
 use super::token::{Lexer, Token};
@@ -33,7 +33,42 @@ impl From<lalrpop_util::ParseError<ByteIndex, Token, ()>> for ParseError {
 
 impl ToDiagnostic for ParseError {
     fn to_diagnostic(&self) -> Diagnostic {
-        unimplemented!()
+        match &self {
+            ParseError::LalrPop(lalrpop_util::ParseError::InvalidToken { location }) => {
+                let span = SourceSpan::new(*location, *location);
+                Diagnostic::error()
+                    .with_message("invalid token")
+                    .with_labels(vec![Label::primary(span.source_id(), span)
+                        .with_message("this token is not valid in this position")])
+            }
+            ParseError::LalrPop(lalrpop_util::ParseError::UnrecognizedToken {
+                token: (l, token, r),
+                expected,
+            }) => {
+                let span = SourceSpan::new(*l, *r);
+                Diagnostic::error()
+                    .with_message(format!("unexpected token `{:?}`", token))
+                    .with_labels(vec![Label::primary(span.source_id(), span)
+                        .with_message(format!("expected one of: {}", expected.join(", ")))])
+            }
+            ParseError::LalrPop(lalrpop_util::ParseError::UnrecognizedEOF { location, expected }) => {
+                let span = SourceSpan::new(*location, *location);
+                Diagnostic::error()
+                    .with_message("unexpected end of file")
+                    .with_labels(vec![Label::primary(span.source_id(), span)
+                        .with_message(format!("expected one of: {}", expected.join(", ")))])
+            }
+            ParseError::LalrPop(lalrpop_util::ParseError::ExtraToken { token: (l, token, r) }) => {
+                let span = SourceSpan::new(*l, *r);
+                Diagnostic::error()
+                    .with_message(format!("extra token `{:?}`", token))
+                    .with_labels(vec![Label::primary(span.source_id(), span)
+                        .with_message("this token was not expected here")])
+            }
+            ParseError::LalrPop(lalrpop_util::ParseError::User { .. }) => {
+                Diagnostic::error().with_message("invalid term")
+            }
+        }
     }
 }
 
@@ -61,6 +96,22 @@ impl Parse for ast::Root {
         Self::parse_tokens(errors, lexer)
     }
 
+    // Tries the whole token stream as a single `Root` first, so a
+    // clean file keeps parsing exactly as before. If that fails,
+    // rather than reporting only the first failure and giving up on
+    // the rest of the file, re-parses form-at-a-time: the stream is
+    // split on `Token::Dot` (each `.`-terminated top-level term) and
+    // every form is parsed independently, so one malformed
+    // `{function,...}` no longer hides every other error in the file.
+    //
+    // Reconstructing a single recovered `Root` from the forms that did
+    // parse still needs `error` recovery productions in the grammar
+    // itself - that's what would let a failing form be represented as
+    // a placeholder node instead of losing the whole parse - and the
+    // `.lalrpop` grammar source isn't part of this crate snapshot, so
+    // this still returns `Err(())` once the whole file has been
+    // checked, but every form's diagnostic has been reported through
+    // `errors` by then instead of just the first.
     fn parse_tokens<S>(
         errors: &mut dyn ErrorReceiver<E = ParseError, W = ParseError>,
         tokens: S,
@@ -68,21 +119,56 @@ impl Parse for ast::Root {
     where
         S: IntoIterator<Item = Self::Token>,
     {
-        match Self::Parser::new().parse(tokens) {
-            Ok(inner) => Ok(inner),
-            Err(err) => {
-                errors.error(err.into());
-                Err(())
-            },
+        let tokens: Vec<Self::Token> = tokens.into_iter().collect();
+
+        if let Ok(inner) = Self::Parser::new().parse(tokens.iter().cloned()) {
+            return Ok(inner);
+        }
+
+        let mut form = Vec::new();
+        let mut last_end = None;
+        for token in tokens {
+            let dot_end = match &token {
+                Ok((_, Token::Dot, end)) => Some(*end),
+                _ => None,
+            };
+            if let Ok((_, _, end)) = &token {
+                last_end = Some(*end);
+            }
+            form.push(token);
+            if let Some(end) = dot_end {
+                let mut chunk = std::mem::take(&mut form);
+                chunk.push(Ok((end, Token::EOF, end)));
+                if let Err(err) = Self::Parser::new().parse(chunk) {
+                    errors.error(err.into());
+                }
+            }
         }
+
+        // A file that doesn't end with a trailing `.` would otherwise
+        // have this last, dot-less form dropped here without ever
+        // being parsed or reported - the opposite of this recovery
+        // loop's job of surfacing every structural error in the file.
+        if !form.is_empty() {
+            if let Some(end) = last_end {
+                let mut chunk = std::mem::take(&mut form);
+                chunk.push(Ok((end, Token::EOF, end)));
+                if let Err(err) = Self::Parser::new().parse(chunk) {
+                    errors.error(err.into());
+                }
+            }
+        }
+
+        Err(())
     }
 
 }
 
 #[cfg(test)]
 mod test {
-    use libeir_util_parse::{Parser, Parse, Errors, ArcCodemap};
-    use super::ParseError;
+    use libeir_util_parse::{Parser, Parse, ErrorReceiver, Errors, ArcCodemap, ToDiagnostic};
+    use libeir_diagnostics::ByteIndex;
+    use super::{ParseError, Token};
     use super::ast::Root;
 
     fn parse<'a, T>(input: &'a str) -> T
@@ -135,4 +221,88 @@ mod test {
 ");
     }
 
+    /// Collects every error handed to it instead of printing, so tests
+    /// can assert on how many (and which) forms a recovery pass
+    /// reported rather than only on the overall `Result`.
+    #[derive(Default)]
+    struct CollectingErrors(Vec<ParseError>);
+
+    impl ErrorReceiver for CollectingErrors {
+        type E = ParseError;
+        type W = ParseError;
+
+        fn error(&mut self, err: ParseError) {
+            self.0.push(err);
+        }
+
+        fn warning(&mut self, warn: ParseError) {
+            self.0.push(warn);
+        }
+    }
+
+    fn tok(kind: Token, start: u32, end: u32) -> Result<(ByteIndex, Token, ByteIndex), ()> {
+        Ok((ByteIndex::from(start), kind, ByteIndex::from(end)))
+    }
+
+    #[test]
+    fn a_malformed_form_does_not_hide_a_well_formed_one_later_in_the_file() {
+        let tokens = vec![
+            // {woo, foo}.
+            tok(Token::LBrace, 0, 1),
+            tok(Token::Atom("woo".to_string()), 1, 4),
+            tok(Token::Comma, 4, 5),
+            tok(Token::Atom("foo".to_string()), 5, 8),
+            tok(Token::RBrace, 8, 9),
+            tok(Token::Dot, 9, 10),
+            // a bare `,` is not a valid top-level form
+            tok(Token::Comma, 11, 12),
+            tok(Token::Dot, 12, 13),
+            // {ok}. - and, crucially, no trailing `.` after it
+            tok(Token::LBrace, 14, 15),
+            tok(Token::Atom("ok".to_string()), 15, 17),
+            tok(Token::RBrace, 17, 18),
+        ];
+
+        let mut errors = CollectingErrors::default();
+        let result = Root::parse_tokens(&mut errors, tokens);
+
+        assert!(result.is_err());
+        assert_eq!(
+            errors.0.len(),
+            1,
+            "only the malformed middle form should have been reported, \
+             and the dot-less trailing form should still have been parsed \
+             (not silently dropped)"
+        );
+    }
+
+    #[test]
+    fn to_diagnostic_renders_every_lalrpop_error_variant_without_panicking() {
+        let variants = vec![
+            ParseError::LalrPop(lalrpop_util::ParseError::InvalidToken {
+                location: ByteIndex::from(0),
+            }),
+            ParseError::LalrPop(lalrpop_util::ParseError::UnrecognizedToken {
+                token: (ByteIndex::from(0), Token::Dot, ByteIndex::from(1)),
+                expected: vec!["an atom".to_string()],
+            }),
+            ParseError::LalrPop(lalrpop_util::ParseError::UnrecognizedEOF {
+                location: ByteIndex::from(0),
+                expected: vec!["`.`".to_string()],
+            }),
+            ParseError::LalrPop(lalrpop_util::ParseError::ExtraToken {
+                token: (ByteIndex::from(0), Token::Dot, ByteIndex::from(1)),
+            }),
+            ParseError::LalrPop(lalrpop_util::ParseError::User { error: () }),
+        ];
+
+        for variant in &variants {
+            // Just exercising `to_diagnostic` end to end: `Diagnostic`
+            // is built by an external crate not part of this
+            // snapshot, so its rendered fields aren't inspectable
+            // here, but every branch of `ParseError` should still
+            // build one without panicking.
+            let _ = variant.to_diagnostic();
+        }
+    }
 }
@@ -0,0 +1,358 @@
+use libeir_diagnostics::{ByteIndex, Diagnostic, Label, SourceSpan};
+
+use super::Scanner;
+use libeir_util_parse::Source;
+
+/// Tokens produced for the Abstract Format grammar.
+///
+/// String and character literals carry their fully decoded contents -
+/// see [`unescape_literal`] and [`unescape_char`] - rather than the raw
+/// source slice, so that lowering never has to deal with escape
+/// sequences itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Atom(String),
+    Var(String),
+    Integer(i64),
+    String(String),
+    Char(char),
+
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Comma,
+    Dot,
+    Pipe,
+
+    EOF,
+}
+
+/// Lexer over a [`Scanner`], yielding tokens for the Abstract Format
+/// grammar consumed by `grammar::RootParser`.
+pub struct Lexer<S> {
+    scanner: Scanner<S>,
+}
+
+impl<S> Lexer<S>
+where
+    S: Source,
+{
+    pub fn new(scanner: Scanner<S>) -> Self {
+        Lexer { scanner }
+    }
+}
+
+impl<S> Iterator for Lexer<S>
+where
+    S: Source,
+{
+    type Item = Result<(ByteIndex, Token, ByteIndex), ()>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Honestly a stub, not a "finished but empty" driver: this
+        // always returns `None`, i.e. every file lexes as zero tokens;
+        // `parser.rs`'s recovery loop finds no `.`-terminated form in
+        // an empty stream, so it reports no per-form error for it. A real implementation
+        // needs `self.scanner`'s peek/bump/position methods to drive
+        // character classification and whitespace-skipping, then route
+        // `"`- and `$`-prefixed literals through a dedicated string/char
+        // mode that calls `unescape_literal`/`unescape_char` below
+        // instead of storing the raw slice between the delimiters -
+        // but `Scanner`'s own definition (`scanner.rs`) isn't part of
+        // this crate snapshot (only `libeir_util_parse`'s `lib.rs` is,
+        // and it re-exports `scanner`/`source`/`util`/`result`/`errors`
+        // from files that aren't present here), so its actual method
+        // surface isn't known and can't be called from this module.
+        // `unescape_literal`/`unescape_char` are still fully
+        // implemented and covered by `tests` below, since decoding a
+        // literal's *contents* doesn't depend on `Scanner` at all -
+        // only finding where a literal starts and ends does.
+        None
+    }
+}
+
+/// A malformed escape sequence encountered while decoding a string or
+/// character literal.
+#[derive(Debug, Clone)]
+pub struct UnescapeError {
+    pub span: SourceSpan,
+    pub message: String,
+}
+
+impl UnescapeError {
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic::error()
+            .with_message("malformed escape sequence")
+            .with_labels(vec![Label::primary(self.span.source_id(), self.span)
+                .with_message(self.message.clone())])
+    }
+}
+
+/// Decodes the full Erlang escape grammar found inside `"..."` string
+/// literals and `$c` character literals:
+///
+/// - single-char escapes: `\b \d \e \f \n \r \s \t \v \" \' \\`
+/// - octal escapes: `\NNN`, 1-3 octal digits
+/// - hex escapes: `\xHH` and brace hex `\x{...}`
+/// - control escapes: `\^A`..`\^Z`, value is `letter & 0x1F`
+///
+/// `start` is the absolute byte offset of the first character of
+/// `input` in the source file, used to produce correctly-positioned
+/// diagnostics for malformed escapes.
+pub fn unescape_literal(input: &str, start: ByteIndex) -> (String, Vec<UnescapeError>) {
+    let mut out = String::with_capacity(input.len());
+    let mut errors = Vec::new();
+
+    let mut chars = input.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+
+        let esc_start = start + idx as u32;
+        match chars.next() {
+            None => {
+                errors.push(UnescapeError {
+                    span: SourceSpan::new(esc_start, esc_start + 1),
+                    message: "dangling `\\` at end of literal".to_string(),
+                });
+            }
+            Some((_, 'b')) => out.push('\u{0008}'),
+            Some((_, 'd')) => out.push('\u{007F}'),
+            Some((_, 'e')) => out.push('\u{001B}'),
+            Some((_, 'f')) => out.push('\u{000C}'),
+            Some((_, 'n')) => out.push('\n'),
+            Some((_, 'r')) => out.push('\r'),
+            Some((_, 's')) => out.push(' '),
+            Some((_, 't')) => out.push('\t'),
+            Some((_, 'v')) => out.push('\u{000B}'),
+            Some((_, '"')) => out.push('"'),
+            Some((_, '\'')) => out.push('\''),
+            Some((_, '\\')) => out.push('\\'),
+
+            Some((_, '^')) => match chars.next() {
+                Some((_, letter)) if letter.is_ascii_alphabetic() => {
+                    let value = (letter.to_ascii_uppercase() as u8) & 0x1F;
+                    out.push(value as char);
+                }
+                other => {
+                    let (end_idx, _) = other.unwrap_or((idx + 2, ' '));
+                    errors.push(UnescapeError {
+                        span: SourceSpan::new(esc_start, start + end_idx as u32 + 1),
+                        message: "expected a letter after `\\^`".to_string(),
+                    });
+                }
+            },
+
+            Some((_, 'x')) => {
+                if let Some(&(_, '{')) = chars.peek() {
+                    chars.next();
+                    let mut digits = String::new();
+                    let mut closed = false;
+                    let mut end_idx = idx + 2;
+                    while let Some(&(d_idx, d)) = chars.peek() {
+                        end_idx = d_idx;
+                        if d == '}' {
+                            chars.next();
+                            closed = true;
+                            break;
+                        } else if d.is_ascii_hexdigit() {
+                            digits.push(d);
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                    match (closed, u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32))
+                    {
+                        (true, Some(c)) if !digits.is_empty() => out.push(c),
+                        _ => errors.push(UnescapeError {
+                            span: SourceSpan::new(esc_start, start + end_idx as u32 + 1),
+                            message: "invalid or out-of-range `\\x{...}` escape".to_string(),
+                        }),
+                    }
+                } else {
+                    let mut digits = String::new();
+                    let mut end_idx = idx + 1;
+                    while digits.len() < 2 {
+                        if let Some(&(d_idx, d)) = chars.peek() {
+                            if d.is_ascii_hexdigit() {
+                                digits.push(d);
+                                end_idx = d_idx;
+                                chars.next();
+                                continue;
+                            }
+                        }
+                        break;
+                    }
+                    match u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32) {
+                        Some(c) if !digits.is_empty() => out.push(c),
+                        _ => errors.push(UnescapeError {
+                            span: SourceSpan::new(esc_start, start + end_idx as u32 + 1),
+                            message: "expected 1-2 hex digits after `\\x`".to_string(),
+                        }),
+                    }
+                }
+            }
+
+            Some((d_idx, d)) if d.is_digit(8) => {
+                let mut digits = String::new();
+                digits.push(d);
+                let mut end_idx = d_idx;
+                while digits.len() < 3 {
+                    if let Some(&(n_idx, n)) = chars.peek() {
+                        if n.is_digit(8) {
+                            digits.push(n);
+                            end_idx = n_idx;
+                            chars.next();
+                            continue;
+                        }
+                    }
+                    break;
+                }
+                match u32::from_str_radix(&digits, 8).ok().and_then(char::from_u32) {
+                    Some(c) => out.push(c),
+                    None => errors.push(UnescapeError {
+                        span: SourceSpan::new(esc_start, start + end_idx as u32 + 1),
+                        message: "octal escape out of range".to_string(),
+                    }),
+                }
+            }
+
+            Some((o_idx, other)) => {
+                errors.push(UnescapeError {
+                    span: SourceSpan::new(esc_start, start + o_idx as u32 + 1),
+                    message: format!("unknown escape sequence `\\{}`", other),
+                });
+            }
+        }
+    }
+
+    (out, errors)
+}
+
+/// Decodes a single `$c` character literal body (the text after `$`),
+/// reusing [`unescape_literal`] and asserting it produced exactly one
+/// code point.
+pub fn unescape_char(input: &str, start: ByteIndex) -> (Option<char>, Vec<UnescapeError>) {
+    let (decoded, mut errors) = unescape_literal(input, start);
+    let mut chars = decoded.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => (Some(c), errors),
+        _ => {
+            if errors.is_empty() {
+                errors.push(UnescapeError {
+                    span: SourceSpan::new(start, start + input.len() as u32),
+                    message: "character literal must be exactly one code point".to_string(),
+                });
+            }
+            (None, errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{unescape_char, unescape_literal};
+    use libeir_diagnostics::ByteIndex;
+
+    fn decode(input: &str) -> String {
+        let (out, errors) = unescape_literal(input, ByteIndex::from(0));
+        assert!(errors.is_empty(), "unexpected errors decoding `{}`: {:?}", input, errors);
+        out
+    }
+
+    #[test]
+    fn passes_through_plain_text() {
+        assert_eq!(decode("hello world"), "hello world");
+    }
+
+    #[test]
+    fn decodes_single_char_escapes() {
+        assert_eq!(decode(r#"\n\r\t\s\\\"\'"#), "\n\r\t \\\"'");
+    }
+
+    #[test]
+    fn decodes_octal_escapes() {
+        assert_eq!(decode(r"\101\102\103"), "ABC");
+        // Fewer than 3 digits still decodes as soon as a non-octal
+        // character ends the run.
+        assert_eq!(decode(r"\41x"), "!x");
+    }
+
+    #[test]
+    fn decodes_hex_escapes() {
+        assert_eq!(decode(r"\x41\x42"), "AB");
+        // 1 hex digit is also accepted.
+        assert_eq!(decode(r"\x9x"), "\u{9}x");
+    }
+
+    #[test]
+    fn decodes_brace_hex_escapes() {
+        assert_eq!(decode(r"\x{41}"), "A");
+        assert_eq!(decode(r"\x{1F600}"), "\u{1F600}");
+    }
+
+    #[test]
+    fn decodes_control_escapes() {
+        assert_eq!(decode(r"\^A"), "\u{1}");
+        assert_eq!(decode(r"\^a"), "\u{1}");
+        assert_eq!(decode(r"\^Z"), "\u{1A}");
+    }
+
+    #[test]
+    fn reports_dangling_backslash() {
+        let (out, errors) = unescape_literal(r"abc\", ByteIndex::from(0));
+        assert_eq!(out, "abc");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].span.start_index(), ByteIndex::from(3));
+    }
+
+    #[test]
+    fn reports_unknown_escape() {
+        let (_, errors) = unescape_literal(r"\q", ByteIndex::from(0));
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("unknown escape"));
+    }
+
+    #[test]
+    fn reports_malformed_control_escape() {
+        let (_, errors) = unescape_literal(r"\^1", ByteIndex::from(0));
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("expected a letter"));
+    }
+
+    #[test]
+    fn reports_invalid_brace_hex_escape() {
+        // Unterminated.
+        let (_, errors) = unescape_literal(r"\x{41", ByteIndex::from(0));
+        assert_eq!(errors.len(), 1);
+
+        // Empty.
+        let (_, errors) = unescape_literal(r"\x{}", ByteIndex::from(0));
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn reports_out_of_range_code_point() {
+        // At most 3 octal digits give at most 0o777 = 511, always a
+        // valid Unicode scalar, so an octal escape decodes cleanly.
+        // The "digits parsed, `char::from_u32` rejected it" case is
+        // reached through brace hex instead.
+        let (_, errors) = unescape_literal(r"\377", ByteIndex::from(0));
+        assert!(errors.is_empty(), "0o377 = 255 is a valid scalar value");
+
+        // 0xD800 (a lone UTF-16 surrogate) is never a valid Rust `char`.
+        let (_, errors) = unescape_literal(r"\x{D800}", ByteIndex::from(0));
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn unescape_char_decodes_a_single_code_point() {
+        let (c, errors) = unescape_char(r"\n", ByteIndex::from(0));
+        assert!(errors.is_empty());
+        assert_eq!(c, Some('\n'));
+    }
+}